@@ -1,6 +1,12 @@
+use crate::text::CleanProfile;
 use anyhow::{Result, anyhow};
-use clap::{ArgAction, ArgGroup, Parser, ValueHint};
-use std::{num::NonZeroUsize, path::PathBuf, thread};
+use clap::{ArgAction, ArgGroup, Parser, Subcommand, ValueEnum, ValueHint};
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    thread,
+};
 
 /// Filter a Parquet file by detected language using lingua + polars + rayon.
 /// Optionally cleans transcriptions by removing non-alphabetic and non-punctuation symbols.
@@ -18,37 +24,190 @@ use std::{num::NonZeroUsize, path::PathBuf, thread};
     name = "babylonify",
     version,
     about = "Filter Parquet rows by detected language using lingua + polars (+ optional cleaning)",
-    group(ArgGroup::new("input_source").required(true).args(&["input", "input_dir"]))
+    group(ArgGroup::new("input_source").required(true).args(&["input", "input_dir", "stdin"])),
+    group(
+        ArgGroup::new("text_processing")
+            .multiple(true)
+            .args(&["clean", "normalize", "apostrophe", "dash", "strip", "fix_homoglyphs"])
+    ),
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 pub struct Cli {
-    /// Input Parquet file path or directory containing Parquet files
-    #[arg(short, long, value_hint = ValueHint::AnyPath)]
-    pub input: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
 
-    /// Input directory with Parquet files (compatibility alias for --input <DIR>)
+    /// Input file path, directory containing input files, or glob pattern. Repeat the flag to
+    /// process several inputs; outputs then go into the --output directory
+    #[arg(short, long, action = ArgAction::Append, value_hint = ValueHint::AnyPath)]
+    pub input: Vec<PathBuf>,
+
+    /// Input directory with input files (compatibility alias for --input <DIR>)
     #[arg(long, value_hint = ValueHint::DirPath)]
     pub input_dir: Option<PathBuf>,
 
-    /// Output Parquet file path (or directory when --input-dir is used)
-    #[arg(short, long, value_hint = ValueHint::FilePath)]
-    pub output: PathBuf,
+    /// Output file path (or directory when the input is a directory, a glob pattern, or
+    /// repeated)
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["split_by_language", "stdin"],
+        value_hint = ValueHint::FilePath
+    )]
+    pub output: Option<PathBuf>,
+
+    /// Optional: read plain-text lines from standard input instead of --input, treating each line
+    /// as a row, and write kept lines to standard output. --output-invalid then names a text file
+    /// for the rejected lines
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        conflicts_with_all = [
+            "output",
+            "split_by_language",
+            "input_format",
+            "output_format",
+            "report",
+            "resume",
+            "max_rows_per_file",
+            "max_bytes_per_file",
+            "coalesce",
+            "annotate",
+            "top_k",
+        ]
+    )]
+    pub stdin: bool,
 
     /// Optional output path for rejected rows (file or directory, matching --output mode)
     #[arg(long, value_hint = ValueHint::AnyPath)]
     pub output_invalid: Option<PathBuf>,
 
+    /// Optional: instead of --output, write rows into one subdirectory per detected language
+    /// (ISO 639-1 code) under this directory. Rows that are empty, undetected, below the
+    /// threshold, or ambiguous go to `und`
+    #[arg(
+        long,
+        value_hint = ValueHint::DirPath,
+        conflicts_with_all = [
+            "output",
+            "output_invalid",
+            "lang",
+            "exclude_lang",
+            "keep_empty",
+            "resume",
+            "max_rows_per_file",
+            "max_bytes_per_file",
+            "coalesce",
+        ]
+    )]
+    pub split_by_language: Option<PathBuf>,
+
+    /// Optional: input file format. Detected from the file extension by default
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub input_format: Option<FileFormat>,
+
+    /// Optional: output file format. Detected from the output file extension by default (outputs
+    /// named after their input keep its extension), falling back to Parquet
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub output_format: Option<FileFormat>,
+
+    /// Optional: write a JSON report with per-file and aggregate row counts, detected language
+    /// counts, a confidence histogram, elapsed time, and the settings used
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub report: Option<PathBuf>,
+
+    /// Optional: descend into subdirectories of a directory input, mirroring their layout under
+    /// the output directories
+    #[arg(short, long, action = ArgAction::SetTrue)]
+    pub recursive: bool,
+
     /// Text column name (default: transcription)
     #[arg(short = 'c', long, default_value = "transcription")]
     pub column: String,
 
-    /// Target language(s) to keep. Repeat the flag to allow multiple languages.
+    /// Target language(s) to keep. Repeat the flag to allow multiple languages. Append
+    /// `:<threshold>` (e.g. `uk:0.85`) to override --threshold for that language.
     #[arg(short = 'l', long, action = ArgAction::Append)]
     pub lang: Vec<String>,
 
-    /// Confidence threshold for keeping a detected language match
+    /// Language(s) to drop instead of keeping --lang targets. Rows whose top detected language
+    /// is excluded above the threshold are rejected; all other rows are kept
+    #[arg(long, action = ArgAction::Append, conflicts_with_all = ["lang", "min_margin"])]
+    pub exclude_lang: Vec<String>,
+
+    /// Candidate languages for the detector (comma-separated or repeated). The --lang targets
+    /// and --exclude-lang languages are always included. Defaults to every language supported by lingua
+    #[arg(long, value_delimiter = ',', action = ArgAction::Append)]
+    pub candidates: Vec<String>,
+
+    /// Confidence threshold for keeping a detected language match, unless overridden per language
     #[arg(long, default_value_t = 0.6, value_parser = parse_threshold)]
     pub threshold: f64,
 
+    /// Optional: minimum confidence gap between the top two detected languages. Rows with a
+    /// smaller gap are rejected as ambiguous
+    #[arg(long, value_parser = parse_margin)]
+    pub min_margin: Option<f64>,
+
+    /// Rows read, filtered, and written per batch. Peak memory grows with the batch size
+    /// rather than the input file size
+    #[arg(long, default_value = "100000", value_name = "N")]
+    pub batch_rows: NonZeroUsize,
+
+    /// Parquet compression codec for kept and invalid outputs
+    #[arg(long, value_enum, default_value_t = Compression::Zstd)]
+    pub compression: Compression,
+
+    /// Optional: codec-specific compression level (zstd 1-22, gzip 0-10, brotli 0-11)
+    #[arg(long, value_name = "LEVEL", allow_negative_numbers = true)]
+    pub compression_level: Option<i32>,
+
+    /// Optional: rows per Parquet row group in the outputs. Defaults to one row group per batch
+    #[arg(long, value_name = "N")]
+    pub row_group_size: Option<NonZeroUsize>,
+
+    /// Column statistics written to outputs: min/max/null counts (basic), none, or full, which
+    /// adds distinct counts. A bare --statistics selects full
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Statistics::Basic,
+        num_args = 0..=1,
+        default_missing_value = "full"
+    )]
+    pub statistics: Statistics,
+
+    /// Optional: re-shard kept rows into part-00000.parquet, part-00001.parquet, ... files of at
+    /// most N rows. --output is then a directory holding one part directory per input file
+    #[arg(long, value_name = "N")]
+    pub max_rows_per_file: Option<NonZeroUsize>,
+
+    /// Optional: like --max-rows-per-file, but caps the estimated uncompressed size of each part.
+    /// Accepts K, M, and G suffixes (e.g. 512M)
+    #[arg(long, value_name = "BYTES", value_parser = parse_byte_size)]
+    pub max_bytes_per_file: Option<NonZeroUsize>,
+
+    /// Optional: write the kept rows of every input into one sequence of part files directly in
+    /// the --output directory, combined with the per-part limits above when given. Inputs are
+    /// written in order, so this requires --file-jobs 1
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub coalesce: bool,
+
+    /// Optional: skip inputs whose output (and invalid output) is already a complete Parquet file
+    /// from an earlier run, so interrupted jobs can be restarted cheaply
+    #[arg(
+        long,
+        visible_alias = "skip-existing",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["max_rows_per_file", "max_bytes_per_file", "coalesce"]
+    )]
+    pub resume: bool,
+
+    /// Number of files processed concurrently when several input files are given. Rows of all
+    /// files share the Rayon thread pool
+    #[arg(long, default_value = "1", value_name = "N")]
+    pub file_jobs: NonZeroUsize,
+
     /// Rayon thread count, defaults to the current core count
     #[arg(long, default_value_t = default_threads())]
     pub threads: usize,
@@ -60,6 +219,194 @@ pub struct Cli {
     /// Optional: clean text (remove everything except alphabetic and punctuation symbols)
     #[arg(long, action = ArgAction::SetTrue)]
     pub clean: bool,
+
+    /// Cleaning profile used by --clean: a built-in profile (default, asr, web, minimal) or the
+    /// path of a TOML file listing custom steps
+    #[arg(
+        long,
+        value_name = "NAME|FILE",
+        default_value = "default",
+        value_parser = parse_clean_profile,
+        requires = "clean"
+    )]
+    pub clean_profile: CleanProfile,
+
+    /// Optional: apply Unicode normalization to the text before cleaning and detection
+    #[arg(long, value_enum, value_name = "FORM")]
+    pub normalize: Option<Normalization>,
+
+    /// Optional: replace apostrophe variants (' ’ ʼ ‘ ` ´ ′) with this character before cleaning
+    /// and detection
+    #[arg(long, value_name = "CHAR")]
+    pub apostrophe: Option<char>,
+
+    /// Optional: replace hyphen and dash variants (- ‐ ‑ ‒ – — ― −) with this character before
+    /// cleaning and detection
+    #[arg(long, value_name = "CHAR")]
+    pub dash: Option<char>,
+
+    /// Optional: remove URLs, e-mail addresses, @mentions, #hashtags, or HTML tags and entities
+    /// (comma-separated or repeated) before homoglyph repair, cleaning, and detection
+    #[arg(long, value_enum, value_delimiter = ',', action = ArgAction::Append, value_name = "KIND")]
+    pub strip: Vec<Markup>,
+
+    /// Optional: in words mixing Latin and Cyrillic letters, replace look-alike letters (a/а,
+    /// e/е, i/і, o/о, p/р, c/с, x/х, ...) with those of the word's dominant script before
+    /// cleaning and detection
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub fix_homoglyphs: bool,
+
+    /// Optional: with --clean or normalization, detect on the processed text but write the
+    /// original text column unchanged
+    #[arg(long, action = ArgAction::SetTrue, requires = "text_processing")]
+    pub keep_original: bool,
+
+    /// Optional: with --clean or normalization, keep the original text column and write the
+    /// processed text to this additional column
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "text_processing",
+        conflicts_with = "stdin"
+    )]
+    pub clean_column: Option<String>,
+
+    /// Optional: append detected_lang and lang_confidence columns. Keeps every row unless
+    /// --output-invalid is also given, in which case rows are still split by the filter
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub annotate: bool,
+
+    /// Optional: append a lang_candidates list column with the top K detected languages and
+    /// their confidence values
+    #[arg(long, value_name = "K")]
+    pub top_k: Option<NonZeroUsize>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print the ISO 639-1 code, ISO 639-3 code, and name of every supported language
+    Languages,
+}
+
+/// File formats accepted by --input-format and --output-format.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Parquet,
+    Csv,
+    Tsv,
+    /// Newline-delimited JSON objects
+    Ndjson,
+    /// Arrow IPC (Feather v2)
+    Ipc,
+}
+
+impl FileFormat {
+    /// Format implied by the extension of `path`, if it is a recognised one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "parquet" | "pq" => Some(Self::Parquet),
+            "csv" => Some(Self::Csv),
+            "tsv" | "tab" => Some(Self::Tsv),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "arrow" | "ipc" | "feather" => Some(Self::Ipc),
+            _ => None,
+        }
+    }
+
+    /// Whether the format can hold list and struct columns.
+    pub fn supports_nested(self) -> bool {
+        !matches!(self, Self::Csv | Self::Tsv)
+    }
+
+    /// Extension used for output files named after their input or numbered parts.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Ndjson => "jsonl",
+            Self::Ipc => "arrow",
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("every file format is a CLI value");
+        f.write_str(value.get_name())
+    }
+}
+
+/// Parquet compression codecs accepted by --compression.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Snappy,
+    Lz4,
+    Gzip,
+    Brotli,
+    None,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("every compression codec is a CLI value");
+        f.write_str(value.get_name())
+    }
+}
+
+/// Unicode normalization forms accepted by --normalize.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Canonical composition, e.g. `и` + combining breve becomes `й`
+    Nfc,
+    /// Compatibility composition, which also folds ligatures, full-width forms, and the like
+    Nfkc,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("every normalization form is a CLI value");
+        f.write_str(value.get_name())
+    }
+}
+
+/// Kinds of markup removed by --strip, listed in the order they are removed.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Markup {
+    /// HTML tags such as `<br/>` and entities such as `&amp;`
+    Html,
+    /// `http://`, `https://`, `ftp://`, and `www.` links
+    Urls,
+    Emails,
+    /// `@name` mentions
+    Mentions,
+    /// `#tag` hashtags
+    Hashtags,
+}
+
+impl fmt::Display for Markup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("every markup kind is a CLI value");
+        f.write_str(value.get_name())
+    }
+}
+
+/// Column statistics levels accepted by --statistics.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statistics {
+    None,
+    Basic,
+    Full,
 }
 
 fn default_threads() -> usize {
@@ -69,19 +416,56 @@ fn default_threads() -> usize {
 }
 
 fn parse_threshold(raw: &str) -> Result<f64> {
-    let threshold: f64 = raw
+    parse_unit_interval(raw, "threshold")
+}
+
+fn parse_margin(raw: &str) -> Result<f64> {
+    parse_unit_interval(raw, "margin")
+}
+
+fn parse_unit_interval(raw: &str, name: &str) -> Result<f64> {
+    let value: f64 = raw
         .parse()
-        .map_err(|_| anyhow!("invalid threshold '{raw}', expected a float between 0.0 and 1.0"))?;
+        .map_err(|_| anyhow!("invalid {name} '{raw}', expected a float between 0.0 and 1.0"))?;
 
-    if (0.0..=1.0).contains(&threshold) {
-        Ok(threshold)
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
     } else {
         Err(anyhow!(
-            "invalid threshold '{raw}', expected a value between 0.0 and 1.0"
+            "invalid {name} '{raw}', expected a value between 0.0 and 1.0"
         ))
     }
 }
 
+fn parse_clean_profile(raw: &str) -> Result<CleanProfile, String> {
+    CleanProfile::parse(raw).map_err(|err| format!("{err:#}"))
+}
+
+fn parse_byte_size(raw: &str) -> Result<NonZeroUsize> {
+    let invalid =
+        || anyhow!("invalid size '{raw}', expected a positive number of bytes such as 64M");
+    let trimmed = raw.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() => {
+            let multiplier = match suffix.to_ascii_uppercase() {
+                'K' => 1 << 10,
+                'M' => 1 << 20,
+                'G' => 1 << 30,
+                _ => return Err(invalid()),
+            };
+            (&trimmed[..index], multiplier)
+        }
+        _ => (trimmed, 1),
+    };
+
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .and_then(NonZeroUsize::new)
+        .ok_or_else(invalid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,6 +484,140 @@ mod tests {
         assert_eq!(cli.threads, default_threads());
         assert_eq!(cli.column, "transcription");
         assert_eq!(cli.output_invalid, None);
+        assert_eq!(cli.min_margin, None);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.batch_rows.get(), 100_000);
+        assert_eq!(cli.file_jobs.get(), 1);
+    }
+
+    #[test]
+    fn cli_languages_subcommand_needs_no_paths() {
+        let cli = Cli::parse_from(["babylonify", "languages"]);
+
+        assert!(matches!(cli.command, Some(Command::Languages)));
+        assert_eq!(cli.output, None);
+    }
+
+    #[test]
+    fn cli_requires_output_without_subcommand() {
+        let err = Cli::try_parse_from(["babylonify", "--input", "in.parquet"]).unwrap_err();
+
+        assert!(err.to_string().contains("--output"));
+    }
+
+    #[test]
+    fn cli_split_by_language_replaces_output() {
+        let cli = Cli::parse_from([
+            "babylonify",
+            "--input",
+            "in.parquet",
+            "--split-by-language",
+            "by_lang",
+        ]);
+
+        assert_eq!(cli.output, None);
+        assert_eq!(cli.split_by_language, Some(PathBuf::from("by_lang")));
+
+        let err = Cli::try_parse_from([
+            "babylonify",
+            "--input",
+            "in.parquet",
+            "--split-by-language",
+            "by_lang",
+            "--output",
+            "out.parquet",
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot be used with"));
+    }
+
+    #[test]
+    fn cli_stdin_replaces_input_and_output() {
+        let cli = Cli::parse_from(["babylonify", "--stdin", "--lang", "uk"]);
+
+        assert!(cli.stdin);
+        assert!(cli.input.is_empty());
+        assert_eq!(cli.output, None);
+
+        let err = Cli::try_parse_from([
+            "babylonify",
+            "--stdin",
+            "--input",
+            "in.parquet",
+            "--output",
+            "out.parquet",
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot be used with"));
+    }
+
+    #[test]
+    fn cli_keep_original_requires_clean() {
+        let err = Cli::try_parse_from([
+            "babylonify",
+            "--input",
+            "in.parquet",
+            "--output",
+            "out.parquet",
+            "--keep-original",
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("--clean"));
+    }
+
+    #[test]
+    fn cli_parses_parquet_write_options() {
+        let cli = Cli::parse_from(["babylonify", "-i", "in.parquet", "-o", "out.parquet"]);
+        assert_eq!(cli.compression, Compression::Zstd);
+        assert_eq!(cli.statistics, Statistics::Basic);
+        assert_eq!(cli.row_group_size, None);
+
+        let cli = Cli::parse_from([
+            "babylonify",
+            "-i",
+            "in.parquet",
+            "-o",
+            "out.parquet",
+            "--compression",
+            "lz4",
+            "--row-group-size",
+            "1000",
+            "--statistics",
+        ]);
+        assert_eq!(cli.compression, Compression::Lz4);
+        assert_eq!(cli.compression.to_string(), "lz4");
+        assert_eq!(cli.statistics, Statistics::Full);
+        assert_eq!(cli.row_group_size, NonZeroUsize::new(1000));
+    }
+
+    #[test]
+    fn file_formats_follow_extensions() {
+        assert_eq!(
+            FileFormat::from_path(Path::new("a/rows.JSONL")),
+            Some(FileFormat::Ndjson)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("rows.feather")),
+            Some(FileFormat::Ipc)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("rows.tab")),
+            Some(FileFormat::Tsv)
+        );
+        assert_eq!(FileFormat::from_path(Path::new("rows.txt")), None);
+        assert_eq!(FileFormat::from_path(Path::new("rows")), None);
+        assert_eq!(FileFormat::Ndjson.extension(), "jsonl");
+    }
+
+    #[test]
+    fn parses_byte_sizes_with_suffixes() {
+        assert_eq!(parse_byte_size("4096").unwrap().get(), 4096);
+        assert_eq!(parse_byte_size("64k").unwrap().get(), 64 << 10);
+        assert_eq!(parse_byte_size("512M").unwrap().get(), 512 << 20);
+        assert_eq!(parse_byte_size("2G").unwrap().get(), 2 << 30);
+        assert!(parse_byte_size("0").is_err());
+        assert!(parse_byte_size("12T").is_err());
+        assert!(parse_byte_size("M").is_err());
     }
 
     #[test]
@@ -120,4 +638,20 @@ mod tests {
         assert!(rendered.contains("0"));
         assert!(rendered.contains("1"));
     }
+
+    #[test]
+    fn cli_rejects_out_of_range_margins() {
+        let err = Cli::try_parse_from([
+            "babylonify",
+            "--input",
+            "in.parquet",
+            "--output",
+            "out.parquet",
+            "--min-margin",
+            "1.5",
+        ])
+        .unwrap_err();
+
+        assert!(err.to_string().contains("invalid margin '1.5'"));
+    }
 }
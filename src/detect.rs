@@ -1,8 +1,96 @@
 use anyhow::{Result, anyhow};
-use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
-use std::collections::HashSet;
+use lingua::{IsoCode639_1, IsoCode639_3, Language, LanguageDetector, LanguageDetectorBuilder};
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+    str::FromStr,
+};
 
-pub fn parse_languages(codes: &[String]) -> Result<HashSet<Language>> {
+/// Target languages to keep or languages to drop (with their confidence thresholds) plus the
+/// candidate languages the detector chooses between. `targets` is empty when `excluded` is set,
+/// and `candidates` is `None` when the detector should consider every language lingua supports.
+#[derive(Debug)]
+pub struct LanguageSelection {
+    pub targets: HashMap<Language, f64>,
+    pub excluded: HashMap<Language, f64>,
+    pub candidates: Option<HashSet<Language>>,
+}
+
+impl LanguageSelection {
+    pub fn parse(
+        targets: &[String],
+        excluded: &[String],
+        candidates: &[String],
+        threshold: f64,
+    ) -> Result<Self> {
+        let (targets, excluded) = if excluded.is_empty() {
+            (parse_languages(targets, threshold)?, HashMap::new())
+        } else {
+            (HashMap::new(), parse_languages(excluded, threshold)?)
+        };
+        let candidates = parse_candidates(candidates, targets.keys().chain(excluded.keys()))?;
+        Ok(Self {
+            targets,
+            excluded,
+            candidates,
+        })
+    }
+
+    /// Selection for `--split-by-language`, which has no targets: the detector chooses between
+    /// the `--candidates` alone, or every supported language when none are given.
+    pub fn for_split(candidates: &[String]) -> Result<Self> {
+        Ok(Self {
+            targets: HashMap::new(),
+            excluded: HashMap::new(),
+            candidates: parse_candidates(candidates, iter::empty())?,
+        })
+    }
+
+    pub fn is_exclusion(&self) -> bool {
+        !self.excluded.is_empty()
+    }
+
+    pub fn describe_targets(&self) -> String {
+        if self.is_exclusion() {
+            format!("all except {}", describe_thresholds(&self.excluded))
+        } else {
+            describe_thresholds(&self.targets)
+        }
+    }
+
+    pub fn describe_candidates(&self) -> String {
+        match &self.candidates {
+            Some(candidates) => {
+                let mut codes: Vec<String> = candidates
+                    .iter()
+                    .map(|language| language.iso_code_639_1().to_string())
+                    .collect();
+                codes.sort();
+                format!("{{{}}}", codes.join(", "))
+            }
+            None => "all".to_string(),
+        }
+    }
+}
+
+/// Format languages with their thresholds as `{en: 0.5, uk: 0.6}`, sorted by ISO 639-1 code so
+/// that log lines are stable across runs.
+pub fn describe_thresholds(thresholds: &HashMap<Language, f64>) -> String {
+    let mut entries: Vec<(String, f64)> = thresholds
+        .iter()
+        .map(|(language, threshold)| (language.iso_code_639_1().to_string(), *threshold))
+        .collect();
+    entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+    let entries: Vec<String> = entries
+        .into_iter()
+        .map(|(code, threshold)| format!("{code}: {threshold}"))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// Parse `--lang` values of the form `<lang>` or `<lang>:<threshold>`; languages without an
+/// explicit threshold use `default_threshold`.
+pub fn parse_languages(codes: &[String], default_threshold: f64) -> Result<HashMap<Language, f64>> {
     let codes = if codes.is_empty() {
         vec!["uk".to_string()]
     } else {
@@ -11,30 +99,164 @@ pub fn parse_languages(codes: &[String]) -> Result<HashSet<Language>> {
 
     codes
         .into_iter()
-        .map(|code| parse_language(&code))
+        .map(|code| parse_target(&code, default_threshold))
         .collect()
 }
 
-pub fn build_detector() -> LanguageDetector {
-    LanguageDetectorBuilder::from_all_languages()
-        .with_preloaded_language_models()
-        .build()
+fn parse_target(code: &str, default_threshold: f64) -> Result<(Language, f64)> {
+    let Some((language, threshold)) = code.rsplit_once(':') else {
+        return Ok((parse_language(code)?, default_threshold));
+    };
+
+    let threshold: f64 = threshold.trim().parse().map_err(|_| {
+        anyhow!("invalid threshold in '{code}', expected a float between 0.0 and 1.0")
+    })?;
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(anyhow!(
+            "invalid threshold in '{code}', expected a value between 0.0 and 1.0"
+        ));
+    }
+
+    Ok((parse_language(language)?, threshold))
+}
+
+fn parse_candidates<'a>(
+    codes: &[String],
+    required: impl Iterator<Item = &'a Language>,
+) -> Result<Option<HashSet<Language>>> {
+    if codes.is_empty() {
+        return Ok(None);
+    }
+
+    let mut candidates = codes
+        .iter()
+        .map(|code| parse_language(code))
+        .collect::<Result<HashSet<_>>>()?;
+    candidates.extend(required.copied());
+
+    if candidates.len() < 2 {
+        return Err(anyhow!(
+            "At least two candidate languages are required, got {:?}",
+            candidates
+        ));
+    }
+
+    Ok(Some(candidates))
+}
+
+pub fn build_detector(languages: &LanguageSelection) -> LanguageDetector {
+    let mut builder = match &languages.candidates {
+        Some(candidates) => {
+            LanguageDetectorBuilder::from_languages(&candidates.iter().copied().collect::<Vec<_>>())
+        }
+        None => LanguageDetectorBuilder::from_all_languages(),
+    };
+
+    builder.with_preloaded_language_models().build()
 }
 
 fn parse_language(code: &str) -> Result<Language> {
     let code = code.trim().to_lowercase();
-    match code.as_str() {
-        "uk" | "ukr" | "ukrainian" | "українська" => Ok(Language::Ukrainian),
-        "en" | "eng" | "english" => Ok(Language::English),
-        "ru" | "rus" | "russian" | "русский" => Ok(Language::Russian),
-        "pl" | "polish" => Ok(Language::Polish),
-        "de" | "german" => Ok(Language::German),
-        "fr" | "french" => Ok(Language::French),
-        "es" | "spanish" => Ok(Language::Spanish),
-        other => lingua::Language::all()
-            .into_iter()
-            .find(|language| format!("{language:?}").to_lowercase() == other)
-            .ok_or_else(|| anyhow!("Unknown language: '{}'", code)),
+    if let Ok(iso_code) = IsoCode639_1::from_str(&code) {
+        return Ok(Language::from_iso_code_639_1(&iso_code));
+    }
+    if let Ok(iso_code) = IsoCode639_3::from_str(&code) {
+        return Ok(Language::from_iso_code_639_3(&iso_code));
+    }
+
+    Language::all()
+        .into_iter()
+        .find(|language| {
+            language.to_string().to_lowercase() == code || native_name(*language) == code
+        })
+        .ok_or_else(|| anyhow!("Unknown language: '{}'", code))
+}
+
+/// Every language supported by this build, sorted by ISO 639-1 code.
+pub fn supported_languages() -> Vec<Language> {
+    let mut languages: Vec<Language> = Language::all().into_iter().collect();
+    languages.sort_by_key(|language| language.iso_code_639_1().to_string());
+    languages
+}
+
+/// Lowercase endonym of `language`, accepted by `--lang` alongside ISO codes and English names.
+pub fn native_name(language: Language) -> &'static str {
+    match language {
+        Language::Afrikaans => "afrikaans",
+        Language::Albanian => "shqip",
+        Language::Arabic => "العربية",
+        Language::Armenian => "հայերեն",
+        Language::Azerbaijani => "azərbaycan",
+        Language::Basque => "euskara",
+        Language::Belarusian => "беларуская",
+        Language::Bengali => "বাংলা",
+        Language::Bokmal => "bokmål",
+        Language::Bosnian => "bosanski",
+        Language::Bulgarian => "български",
+        Language::Catalan => "català",
+        Language::Chinese => "中文",
+        Language::Croatian => "hrvatski",
+        Language::Czech => "čeština",
+        Language::Danish => "dansk",
+        Language::Dutch => "nederlands",
+        Language::English => "english",
+        Language::Esperanto => "esperanto",
+        Language::Estonian => "eesti",
+        Language::Finnish => "suomi",
+        Language::French => "français",
+        Language::Ganda => "luganda",
+        Language::Georgian => "ქართული",
+        Language::German => "deutsch",
+        Language::Greek => "ελληνικά",
+        Language::Gujarati => "ગુજરાતી",
+        Language::Hebrew => "עברית",
+        Language::Hindi => "हिन्दी",
+        Language::Hungarian => "magyar",
+        Language::Icelandic => "íslenska",
+        Language::Indonesian => "bahasa indonesia",
+        Language::Irish => "gaeilge",
+        Language::Italian => "italiano",
+        Language::Japanese => "日本語",
+        Language::Kazakh => "қазақ тілі",
+        Language::Korean => "한국어",
+        Language::Latin => "latina",
+        Language::Latvian => "latviešu",
+        Language::Lithuanian => "lietuvių",
+        Language::Macedonian => "македонски",
+        Language::Malay => "bahasa melayu",
+        Language::Maori => "te reo māori",
+        Language::Marathi => "मराठी",
+        Language::Mongolian => "монгол",
+        Language::Nynorsk => "nynorsk",
+        Language::Persian => "فارسی",
+        Language::Polish => "polski",
+        Language::Portuguese => "português",
+        Language::Punjabi => "ਪੰਜਾਬੀ",
+        Language::Romanian => "română",
+        Language::Russian => "русский",
+        Language::Serbian => "српски",
+        Language::Shona => "chishona",
+        Language::Slovak => "slovenčina",
+        Language::Slovene => "slovenščina",
+        Language::Somali => "soomaali",
+        Language::Sotho => "sesotho",
+        Language::Spanish => "español",
+        Language::Swahili => "kiswahili",
+        Language::Swedish => "svenska",
+        Language::Tagalog => "tagalog",
+        Language::Tamil => "தமிழ்",
+        Language::Telugu => "తెలుగు",
+        Language::Thai => "ไทย",
+        Language::Tsonga => "xitsonga",
+        Language::Tswana => "setswana",
+        Language::Turkish => "türkçe",
+        Language::Ukrainian => "українська",
+        Language::Urdu => "اردو",
+        Language::Vietnamese => "tiếng việt",
+        Language::Welsh => "cymraeg",
+        Language::Xhosa => "isixhosa",
+        Language::Yoruba => "yorùbá",
+        Language::Zulu => "isizulu",
     }
 }
 
@@ -59,20 +281,139 @@ mod tests {
         assert!(msg.contains("Unknown language"));
     }
 
+    #[test]
+    fn parse_language_accepts_any_iso_code() {
+        assert_eq!(parse_language("it").unwrap(), Language::Italian);
+        assert_eq!(parse_language("bel").unwrap(), Language::Belarusian);
+        assert_eq!(parse_language("KAZ").unwrap(), Language::Kazakh);
+        assert_eq!(parse_language("nb").unwrap(), Language::Bokmal);
+    }
+
+    #[test]
+    fn parse_language_accepts_native_names() {
+        assert_eq!(parse_language("Italiano").unwrap(), Language::Italian);
+        assert_eq!(parse_language("беларуская").unwrap(), Language::Belarusian);
+        assert_eq!(parse_language("Ελληνικά").unwrap(), Language::Greek);
+        assert_eq!(parse_language("日本語").unwrap(), Language::Japanese);
+    }
+
+    #[test]
+    fn every_supported_language_round_trips() {
+        for language in supported_languages() {
+            let iso_639_1 = language.iso_code_639_1().to_string();
+            let iso_639_3 = language.iso_code_639_3().to_string();
+            assert_eq!(parse_language(&iso_639_1).unwrap(), language);
+            assert_eq!(parse_language(&iso_639_3).unwrap(), language);
+            assert_eq!(parse_language(&language.to_string()).unwrap(), language);
+            assert_eq!(parse_language(native_name(language)).unwrap(), language);
+        }
+    }
+
     #[test]
     fn parse_languages_defaults_to_ukrainian() {
-        let langs = parse_languages(&[]).unwrap();
+        let langs = parse_languages(&[], 0.6).unwrap();
         assert_eq!(langs.len(), 1);
-        assert!(langs.contains(&Language::Ukrainian));
+        assert_eq!(langs.get(&Language::Ukrainian), Some(&0.6));
+    }
+
+    #[test]
+    fn parse_languages_supports_per_language_thresholds() {
+        let langs = parse_languages(
+            &[
+                "uk:0.85".to_string(),
+                "en:0.5".to_string(),
+                "ru".to_string(),
+            ],
+            0.6,
+        )
+        .unwrap();
+        assert_eq!(langs.get(&Language::Ukrainian), Some(&0.85));
+        assert_eq!(langs.get(&Language::English), Some(&0.5));
+        assert_eq!(langs.get(&Language::Russian), Some(&0.6));
+    }
+
+    #[test]
+    fn parse_languages_rejects_invalid_thresholds() {
+        let err = parse_languages(&["uk:1.5".to_string()], 0.6).unwrap_err();
+        assert!(err.to_string().contains("uk:1.5"));
+
+        let err = parse_languages(&["uk:high".to_string()], 0.6).unwrap_err();
+        assert!(err.to_string().contains("uk:high"));
+    }
+
+    #[test]
+    fn candidates_default_to_all_languages() {
+        let languages = LanguageSelection::parse(&["uk".to_string()], &[], &[], 0.6).unwrap();
+        assert_eq!(languages.candidates, None);
+        assert_eq!(languages.describe_candidates(), "all");
+    }
+
+    #[test]
+    fn candidates_always_include_targets() {
+        let languages = LanguageSelection::parse(
+            &["uk".to_string(), "en".to_string()],
+            &[],
+            &["ru".to_string(), "be".to_string()],
+            0.6,
+        )
+        .unwrap();
+        let candidates = languages.candidates.unwrap();
+        assert_eq!(candidates.len(), 4);
+        assert!(candidates.contains(&Language::Ukrainian));
+        assert!(candidates.contains(&Language::English));
+        assert!(candidates.contains(&Language::Russian));
+        assert!(candidates.contains(&Language::Belarusian));
+    }
+
+    #[test]
+    fn exclusions_replace_default_targets() {
+        let languages =
+            LanguageSelection::parse(&[], &["ru".to_string()], &["uk".to_string()], 0.6).unwrap();
+        assert!(languages.is_exclusion());
+        assert!(languages.targets.is_empty());
+        assert_eq!(languages.excluded.get(&Language::Russian), Some(&0.6));
+        assert!(languages.describe_targets().starts_with("all except"));
+        assert!(languages.candidates.unwrap().contains(&Language::Russian));
+    }
+
+    #[test]
+    fn descriptions_are_sorted_by_iso_code() {
+        let languages = LanguageSelection::parse(
+            &["uk:0.85".to_string(), "en".to_string()],
+            &[],
+            &["ru".to_string(), "be".to_string()],
+            0.6,
+        )
+        .unwrap();
+        assert_eq!(languages.describe_targets(), "{en: 0.6, uk: 0.85}");
+        assert_eq!(languages.describe_candidates(), "{be, en, ru, uk}");
+    }
+
+    #[test]
+    fn split_selection_has_no_default_target() {
+        let languages =
+            LanguageSelection::for_split(&["en".to_string(), "ru".to_string()]).unwrap();
+        assert!(languages.targets.is_empty());
+        assert_eq!(languages.candidates.unwrap().len(), 2);
+
+        let err = LanguageSelection::for_split(&["en".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("At least two candidate languages"));
+    }
+
+    #[test]
+    fn candidates_require_more_than_one_language() {
+        let err = LanguageSelection::parse(&["uk".to_string()], &[], &["uk".to_string()], 0.6)
+            .unwrap_err();
+        assert!(err.to_string().contains("At least two candidate languages"));
     }
 
     #[test]
     fn parse_languages_supports_multiple_values() {
         let langs =
-            parse_languages(&["uk".to_string(), "en".to_string(), "ru".to_string()]).unwrap();
+            parse_languages(&["uk".to_string(), "en".to_string(), "ru".to_string()], 0.6).unwrap();
         assert_eq!(langs.len(), 3);
-        assert!(langs.contains(&Language::Ukrainian));
-        assert!(langs.contains(&Language::English));
-        assert!(langs.contains(&Language::Russian));
+        assert!(langs.contains_key(&Language::Ukrainian));
+        assert!(langs.contains_key(&Language::English));
+        assert!(langs.contains_key(&Language::Russian));
     }
 }
@@ -0,0 +1,676 @@
+use crate::cli::{Compression, FileFormat, Statistics};
+use anyhow::{Context, Result, anyhow};
+use log::info;
+use polars::{
+    io::{
+        csv::write::BatchedWriter as CsvBatchedWriter,
+        ipc::BatchedWriter as IpcBatchedWriter,
+        json::BatchedWriter as JsonBatchedWriter,
+        parquet::write::{BatchedWriter, KeyValueMetadata},
+    },
+    prelude::*,
+};
+use polars_arrow::io::ipc::write::default_ipc_fields;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// File-level Parquet metadata written into the footer of every output. The footer is only written
+/// once all batches succeeded, so its presence marks the output as complete.
+const COMPLETION_KEY: &str = "babylonify.status";
+const COMPLETION_VALUE: &str = "complete";
+
+/// Whether `path` holds a complete output from an earlier run. Parquet outputs must carry the
+/// completion marker; other formats only appear under their final name once fully written.
+pub fn is_complete_output(path: &Path, format: FileFormat) -> bool {
+    if format != FileFormat::Parquet {
+        return path.is_file();
+    }
+
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let mut reader = ParquetReader::new(file);
+    let Ok(metadata) = reader.get_metadata() else {
+        return false;
+    };
+
+    metadata
+        .key_value_metadata
+        .iter()
+        .flatten()
+        .any(|kv| kv.key == COMPLETION_KEY && kv.value.as_deref() == Some(COMPLETION_VALUE))
+}
+
+/// Batches of at most `batch_rows` rows from an input file of any supported format.
+pub enum InputBatches {
+    Parquet(ParquetBatches),
+    Loaded(FrameBatches),
+}
+
+impl InputBatches {
+    pub fn open(path: &Path, format: FileFormat, batch_rows: usize) -> Result<Self> {
+        match format {
+            FileFormat::Parquet => Ok(Self::Parquet(ParquetBatches::open(path, batch_rows)?)),
+            _ => Ok(Self::Loaded(FrameBatches::new(
+                read_frame(path, format)?,
+                batch_rows,
+            ))),
+        }
+    }
+}
+
+impl Iterator for InputBatches {
+    type Item = Result<DataFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Parquet(batches) => batches.next(),
+            Self::Loaded(batches) => batches.next().map(Ok),
+        }
+    }
+}
+
+/// Read a whole input file. Only Parquet inputs are streamed; CSV, TSV, NDJSON, and IPC inputs
+/// are loaded into memory before being sliced into batches.
+fn read_frame(path: &Path, format: FileFormat) -> Result<DataFrame> {
+    let open = || File::open(path).with_context(|| format!("Cannot open {:?}", path));
+    let df = match format {
+        FileFormat::Parquet => ParquetReader::new(open()?).finish()?,
+        FileFormat::Csv | FileFormat::Tsv => CsvReadOptions::default()
+            .with_has_header(true)
+            .map_parse_options(|options| options.with_separator(csv_separator(format)))
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+            .finish()?,
+        FileFormat::Ndjson => JsonLineReader::new(open()?).finish()?,
+        FileFormat::Ipc => IpcReader::new(open()?).finish()?,
+    };
+
+    Ok(df)
+}
+
+fn csv_separator(format: FileFormat) -> u8 {
+    if format == FileFormat::Tsv {
+        b'\t'
+    } else {
+        b','
+    }
+}
+
+/// Slices an in-memory DataFrame into batches. Like [`ParquetBatches`], always yields at least
+/// one (possibly empty) batch.
+pub struct FrameBatches {
+    df: DataFrame,
+    batch_rows: usize,
+    offset: usize,
+    started: bool,
+}
+
+impl FrameBatches {
+    fn new(df: DataFrame, batch_rows: usize) -> Self {
+        Self {
+            df,
+            batch_rows,
+            offset: 0,
+            started: false,
+        }
+    }
+}
+
+impl Iterator for FrameBatches {
+    type Item = DataFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started && self.offset >= self.df.height() {
+            return None;
+        }
+
+        self.started = true;
+        let batch = self.df.slice(self.offset as i64, self.batch_rows);
+        self.offset += self.batch_rows;
+        Some(batch)
+    }
+}
+
+/// Reads a Parquet file in slices of at most `batch_rows` rows, so only the row groups backing
+/// the current slice are decoded. Always yields at least one (possibly empty) batch, which lets
+/// empty inputs still produce outputs carrying the input schema.
+pub struct ParquetBatches {
+    path: PathBuf,
+    metadata: FileMetadataRef,
+    total_rows: usize,
+    batch_rows: usize,
+    offset: usize,
+    started: bool,
+}
+
+impl ParquetBatches {
+    pub fn open(path: &Path, batch_rows: usize) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Cannot open {:?}", path))?;
+        let mut reader = ParquetReader::new(file);
+        let metadata = reader
+            .get_metadata()
+            .with_context(|| format!("Cannot read Parquet metadata from {:?}", path))?
+            .clone();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            total_rows: metadata.num_rows,
+            metadata,
+            batch_rows,
+            offset: 0,
+            started: false,
+        })
+    }
+
+    fn read_batch(&self) -> Result<DataFrame> {
+        let file =
+            File::open(&self.path).with_context(|| format!("Cannot open {:?}", self.path))?;
+        let mut reader = ParquetReader::new(file).with_slice(Some((self.offset, self.batch_rows)));
+        reader.set_metadata(self.metadata.clone());
+        Ok(reader.finish()?)
+    }
+}
+
+impl Iterator for ParquetBatches {
+    type Item = Result<DataFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started && self.offset >= self.total_rows {
+            return None;
+        }
+
+        self.started = true;
+        let batch = self.read_batch();
+        self.offset += self.batch_rows;
+        Some(batch)
+    }
+}
+
+/// Compression, column statistics, and row-group layout applied to Parquet outputs.
+#[derive(Clone, Copy, Debug)]
+pub struct WriteOptions {
+    compression: ParquetCompression,
+    statistics: StatisticsOptions,
+    row_group_size: Option<usize>,
+}
+
+impl WriteOptions {
+    pub fn new(
+        compression: Compression,
+        level: Option<i32>,
+        statistics: Statistics,
+        row_group_size: Option<NonZeroUsize>,
+    ) -> Result<Self> {
+        Ok(Self {
+            compression: parquet_compression(compression, level)?,
+            statistics: match statistics {
+                Statistics::None => StatisticsOptions::empty(),
+                Statistics::Basic => StatisticsOptions::default(),
+                Statistics::Full => StatisticsOptions::full(),
+            },
+            row_group_size: row_group_size.map(NonZeroUsize::get),
+        })
+    }
+}
+
+fn parquet_compression(compression: Compression, level: Option<i32>) -> Result<ParquetCompression> {
+    let invalid_level = |level: i32, range: &str| {
+        anyhow!("Invalid compression level {level} for {compression}, expected {range}")
+    };
+
+    let compression = match (compression, level) {
+        (Compression::Zstd, level) => ParquetCompression::Zstd(
+            level
+                .map(|level| ZstdLevel::try_new(level).map_err(|_| invalid_level(level, "1-22")))
+                .transpose()?,
+        ),
+        (Compression::Gzip, level) => ParquetCompression::Gzip(
+            level
+                .map(|level| {
+                    u8::try_from(level)
+                        .ok()
+                        .and_then(|raw| GzipLevel::try_new(raw).ok())
+                        .ok_or_else(|| invalid_level(level, "0-10"))
+                })
+                .transpose()?,
+        ),
+        (Compression::Brotli, level) => ParquetCompression::Brotli(
+            level
+                .map(|level| {
+                    u32::try_from(level)
+                        .ok()
+                        .and_then(|raw| BrotliLevel::try_new(raw).ok())
+                        .ok_or_else(|| invalid_level(level, "0-11"))
+                })
+                .transpose()?,
+        ),
+        (Compression::Snappy | Compression::Lz4 | Compression::None, Some(_)) => {
+            return Err(anyhow!(
+                "--compression-level is not supported for {compression} compression"
+            ));
+        }
+        (Compression::Snappy, None) => ParquetCompression::Snappy,
+        (Compression::Lz4, None) => ParquetCompression::Lz4Raw,
+        (Compression::None, None) => ParquetCompression::Uncompressed,
+    };
+
+    Ok(compression)
+}
+
+/// Format-specific batched writer behind an [`OutputSink`].
+enum FormatWriter {
+    Parquet(Box<BatchedWriter<File>>),
+    Csv(CsvBatchedWriter<File>),
+    Ipc(IpcBatchedWriter<File>),
+    Ndjson(JsonBatchedWriter<File>),
+}
+
+impl FormatWriter {
+    fn create(
+        file: File,
+        format: FileFormat,
+        options: &WriteOptions,
+        schema: &Schema,
+    ) -> Result<Self> {
+        let writer = match format {
+            FileFormat::Parquet => Self::Parquet(Box::new(
+                ParquetWriter::new(file)
+                    .with_compression(options.compression)
+                    .with_statistics(options.statistics)
+                    .with_key_value_metadata(Some(KeyValueMetadata::from_static(vec![(
+                        COMPLETION_KEY.to_string(),
+                        COMPLETION_VALUE.to_string(),
+                    )])))
+                    .batched(schema)?,
+            )),
+            FileFormat::Csv | FileFormat::Tsv => Self::Csv(
+                CsvWriter::new(file)
+                    .include_header(true)
+                    .with_separator(csv_separator(format))
+                    .batched(schema)?,
+            ),
+            FileFormat::Ipc => {
+                let arrow_schema = schema.to_arrow(CompatLevel::newest());
+                let ipc_fields = default_ipc_fields(arrow_schema.iter_values());
+                Self::Ipc(IpcWriter::new(file).batched(schema, ipc_fields)?)
+            }
+            FileFormat::Ndjson => Self::Ndjson(JsonBatchedWriter::new(file)),
+        };
+
+        Ok(writer)
+    }
+
+    fn write_batch(&mut self, batch: &DataFrame) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => writer.write_batch(batch)?,
+            Self::Csv(writer) => writer.write_batch(batch)?,
+            Self::Ipc(writer) => writer.write_batch(batch)?,
+            Self::Ndjson(writer) => writer.write_batch(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => {
+                writer.finish()?;
+            }
+            Self::Csv(mut writer) => writer.finish()?,
+            Self::Ipc(mut writer) => writer.finish()?,
+            // NDJSON has no footer and every batch is written through unbuffered.
+            Self::Ndjson(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Output written one batch at a time in any supported format. The file is created from the
+/// schema of the first batch, so columns appended during processing are part of the output
+/// schema. Batches go to a hidden temporary sibling that is renamed over `path` only once the
+/// file is complete; an unfinished sink removes its temporary file when dropped.
+///
+/// Each batch becomes its own Parquet row group unless a row-group size is configured, in which
+/// case rows are buffered until a full row group is available.
+pub struct OutputSink {
+    path: PathBuf,
+    temp_path: PathBuf,
+    format: FileFormat,
+    options: WriteOptions,
+    writer: Option<FormatWriter>,
+    pending: Option<DataFrame>,
+    committed: bool,
+}
+
+impl OutputSink {
+    pub fn new(path: &Path, format: FileFormat, options: WriteOptions) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            temp_path: temp_path_for(path),
+            format,
+            options,
+            writer: None,
+            pending: None,
+            committed: false,
+        }
+    }
+
+    pub fn write(&mut self, batch: &DataFrame) -> Result<()> {
+        let row_group_size = self
+            .options
+            .row_group_size
+            .filter(|_| self.format == FileFormat::Parquet);
+        let Some(row_group_size) = row_group_size else {
+            return self.write_row_group(batch);
+        };
+
+        let mut pending = match self.pending.take() {
+            Some(mut pending) => {
+                pending.vstack_mut(batch)?;
+                pending
+            }
+            None => batch.clone(),
+        };
+        while pending.height() >= row_group_size {
+            let row_group = pending.slice(0, row_group_size);
+            self.write_row_group(&row_group)?;
+            pending = pending.slice(row_group_size as i64, pending.height() - row_group_size);
+        }
+
+        // Keep an empty remainder only while nothing has been written, so empty outputs still
+        // get the batch schema.
+        if pending.height() > 0 || self.writer.is_none() {
+            self.pending = Some(pending);
+        }
+        Ok(())
+    }
+
+    /// Write `rows` as one chunk. Filtered batches keep the chunks of the slices they came from
+    /// while appended columns have a single chunk, and the batched writers require all columns
+    /// to be chunked alike.
+    fn write_row_group(&mut self, rows: &DataFrame) -> Result<()> {
+        let mut rows = rows.clone();
+        rows.as_single_chunk_par();
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!(
+                            "Failed to create output directory at '{}'",
+                            parent.display()
+                        )
+                    })?;
+                }
+                let file = File::create(&self.temp_path)
+                    .with_context(|| format!("Cannot create {:?}", self.temp_path))?;
+                let writer = FormatWriter::create(file, self.format, &self.options, rows.schema())?;
+                self.writer.insert(writer)
+            }
+        };
+
+        writer
+            .write_batch(&rows)
+            .with_context(|| format!("Failed to write batch to {:?}", self.temp_path))
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        if let Some(pending) = self.pending.take() {
+            self.write_row_group(&pending)?;
+        }
+
+        if let Some(writer) = self.writer.take() {
+            writer
+                .finish()
+                .with_context(|| format!("Failed to finalize {:?}", self.temp_path))?;
+            fs::rename(&self.temp_path, &self.path).with_context(|| {
+                format!(
+                    "Failed to move {:?} into place at {:?}",
+                    self.temp_path, self.path
+                )
+            })?;
+        }
+
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for OutputSink {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Size limits for one part file of a [`ShardedSink`]. Unset limits never start a new part.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShardLimits {
+    pub max_rows: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+/// Rows re-sharded into `part-00000.<ext>`, `part-00001.<ext>`, ... under `dir`. A new part is
+/// started once the current one reaches `max_rows` rows or `max_bytes` bytes, where bytes are
+/// estimated from the in-memory size of the rows before compression. Every part holds at least
+/// one row, so a single row larger than `max_bytes` still gets written.
+pub struct ShardedSink {
+    dir: PathBuf,
+    format: FileFormat,
+    options: WriteOptions,
+    limits: ShardLimits,
+    current: Option<OutputSink>,
+    parts: usize,
+    rows: usize,
+    bytes: usize,
+}
+
+impl ShardedSink {
+    pub fn new(dir: &Path, format: FileFormat, options: WriteOptions, limits: ShardLimits) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            format,
+            options,
+            limits,
+            current: None,
+            parts: 0,
+            rows: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn write(&mut self, batch: &DataFrame) -> Result<()> {
+        if batch.height() == 0 {
+            // The first part is created even without rows so empty outputs keep the schema.
+            if self.parts == 0 && self.current.is_none() {
+                self.current_part().write(batch)?;
+            }
+            return Ok(());
+        }
+
+        let row_bytes = batch.estimated_size().div_ceil(batch.height()).max(1);
+        let mut offset = 0;
+        while offset < batch.height() {
+            let mut capacity = self.capacity(row_bytes);
+            if capacity == 0 && self.rows > 0 {
+                self.finish_part()?;
+                capacity = self.capacity(row_bytes);
+            }
+            let take = capacity.max(1).min(batch.height() - offset);
+
+            self.current_part()
+                .write(&batch.slice(offset as i64, take))?;
+            self.rows += take;
+            self.bytes += take * row_bytes;
+            offset += take;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        if self.current.is_some() {
+            self.finish_part()?;
+        }
+        Ok(())
+    }
+
+    /// Rows that still fit into the current part.
+    fn capacity(&self, row_bytes: usize) -> usize {
+        let by_rows = self
+            .limits
+            .max_rows
+            .map_or(usize::MAX, |max_rows| max_rows.saturating_sub(self.rows));
+        let by_bytes = self.limits.max_bytes.map_or(usize::MAX, |max_bytes| {
+            max_bytes.saturating_sub(self.bytes) / row_bytes
+        });
+        by_rows.min(by_bytes)
+    }
+
+    fn current_part(&mut self) -> &mut OutputSink {
+        let path = self.dir.join(format!(
+            "part-{:05}.{}",
+            self.parts,
+            self.format.extension()
+        ));
+        let (format, options) = (self.format, self.options);
+        self.current
+            .get_or_insert_with(|| OutputSink::new(&path, format, options))
+    }
+
+    fn finish_part(&mut self) -> Result<()> {
+        if let Some(part) = self.current.take() {
+            part.finish()?;
+            info!(
+                "wrote part {} with {} rows to '{}'",
+                self.parts,
+                self.rows,
+                self.dir.display()
+            );
+            self.parts += 1;
+            self.rows = 0;
+            self.bytes = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Plain-text output written one line at a time. Like [`OutputSink`], lines go to a temporary
+/// sibling that is renamed over `path` by [`LineSink::finish`].
+pub struct LineSink {
+    path: PathBuf,
+    temp_path: PathBuf,
+    writer: BufWriter<File>,
+    committed: bool,
+}
+
+impl LineSink {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "Failed to create output directory at '{}'",
+                    parent.display()
+                )
+            })?;
+        }
+        let temp_path = temp_path_for(path);
+        let file =
+            File::create(&temp_path).with_context(|| format!("Cannot create {:?}", temp_path))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path,
+            writer: BufWriter::new(file),
+            committed: false,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        writeln!(self.writer, "{line}")
+            .with_context(|| format!("Failed to write to {:?}", self.temp_path))
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to finalize {:?}", self.temp_path))?;
+        fs::rename(&self.temp_path, &self.path).with_context(|| {
+            format!(
+                "Failed to move {:?} into place at {:?}",
+                self.temp_path, self.path
+            )
+        })?;
+
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for LineSink {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.{}.tmp", process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_levels_are_validated_per_codec() {
+        assert_eq!(
+            parquet_compression(Compression::Zstd, Some(19)).unwrap(),
+            ParquetCompression::Zstd(Some(ZstdLevel::try_new(19).unwrap()))
+        );
+        assert_eq!(
+            parquet_compression(Compression::None, None).unwrap(),
+            ParquetCompression::Uncompressed
+        );
+
+        let err = parquet_compression(Compression::Gzip, Some(42)).unwrap_err();
+        assert!(err.to_string().contains("expected 0-10"));
+
+        let err = parquet_compression(Compression::Snappy, Some(3)).unwrap_err();
+        assert!(err.to_string().contains("not supported for snappy"));
+    }
+
+    #[test]
+    fn sharded_sink_caps_parts_by_estimated_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let batch = df!["id" => (0..10i64).collect::<Vec<_>>()].unwrap();
+        let row_bytes = batch.estimated_size().div_ceil(batch.height());
+        let options = WriteOptions::new(Compression::Zstd, None, Statistics::Basic, None).unwrap();
+        let limits = ShardLimits {
+            max_rows: None,
+            max_bytes: Some(row_bytes * 4),
+        };
+
+        let mut sink = ShardedSink::new(tmp.path(), FileFormat::Parquet, options, limits);
+        sink.write(&batch).unwrap();
+        sink.finish().unwrap();
+
+        let heights: Vec<usize> = (0..3)
+            .map(|part| {
+                let file = File::open(tmp.path().join(format!("part-{part:05}.parquet"))).unwrap();
+                ParquetReader::new(file).finish().unwrap().height()
+            })
+            .collect();
+        assert_eq!(heights, vec![4, 4, 2]);
+    }
+}
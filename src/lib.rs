@@ -1,9 +1,11 @@
 mod cli;
 mod detect;
+mod io;
 mod process;
+mod report;
 mod text;
 
-pub use cli::Cli;
+pub use cli::{Cli, Command};
 
 use anyhow::{Context, Result};
 use env_logger::Env;
@@ -11,6 +13,11 @@ use log::info;
 use std::sync::Arc;
 
 pub fn run(cli: Cli) -> Result<()> {
+    if let Some(Command::Languages) = cli.command {
+        list_languages();
+        return Ok(());
+    }
+
     init_logging();
 
     info!("configuring Rayon thread pool with {} threads", cli.threads);
@@ -24,16 +31,57 @@ pub fn run(cli: Cli) -> Result<()> {
             )
         })?;
 
-    let target_langs = detect::parse_languages(&cli.lang)?;
+    let languages = if cli.split_by_language.is_some() {
+        detect::LanguageSelection::for_split(&cli.candidates)?
+    } else {
+        detect::LanguageSelection::parse(
+            &cli.lang,
+            &cli.exclude_lang,
+            &cli.candidates,
+            cli.threshold,
+        )?
+    };
+    if cli.split_by_language.is_some() {
+        info!(
+            "splitting rows by detected language with confidence threshold {}",
+            cli.threshold
+        );
+    } else if languages.is_exclusion() {
+        info!(
+            "dropping rows detected as {} excluded language(s) with confidence thresholds {}",
+            languages.excluded.len(),
+            detect::describe_thresholds(&languages.excluded)
+        );
+    } else {
+        info!(
+            "keeping rows matching {} target language(s) with confidence thresholds {}",
+            languages.targets.len(),
+            detect::describe_thresholds(&languages.targets)
+        );
+    }
     info!(
-        "keeping rows matching {} target language(s) with confidence threshold {}",
-        target_langs.len(),
-        cli.threshold
+        "building language detector (candidates = {})",
+        languages.describe_candidates()
     );
-    info!("building language detector");
-    let detector = Arc::new(detect::build_detector());
+    let detector = Arc::new(detect::build_detector(&languages));
+
+    if cli.stdin {
+        process::process_lines(&cli, &languages, &detector)
+    } else {
+        process::process_input(&cli, &languages, &detector)
+    }
+}
 
-    process::process_input(&cli, &target_langs, &detector)
+fn list_languages() {
+    for language in detect::supported_languages() {
+        println!(
+            "{}\t{}\t{}\t{}",
+            language.iso_code_639_1(),
+            language.iso_code_639_3(),
+            language,
+            detect::native_name(language)
+        );
+    }
 }
 
 fn init_logging() {
@@ -1,134 +1,599 @@
-use crate::cli::Cli;
-use crate::text::clean_text;
+use crate::cli::{Cli, FileFormat};
+use crate::detect::LanguageSelection;
+use crate::io::{
+    InputBatches, LineSink, OutputSink, ShardLimits, ShardedSink, WriteOptions, is_complete_output,
+};
+use crate::report::{FileReport, RowStats, RunReport, Settings};
+use crate::text::{Normalizer, fix_homoglyphs, strip_markup};
 use anyhow::{Context, Result, anyhow};
 use lingua::{Language, LanguageDetector};
-use log::info;
-use polars::prelude::*;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use polars::{chunked_array::builder::get_list_builder, prelude::*};
 use rayon::prelude::*;
 use std::{
-    collections::HashSet,
-    fs::{self, File},
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, BufRead, BufWriter, ErrorKind, Write},
+    iter, mem,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::Instant,
 };
+use walkdir::WalkDir;
+
+const DETECTED_LANG_COLUMN: &str = "detected_lang";
+const LANG_CONFIDENCE_COLUMN: &str = "lang_confidence";
+const LANG_CANDIDATES_COLUMN: &str = "lang_candidates";
+const REJECT_REASON_COLUMN: &str = "reject_reason";
+/// Partition for rows without a confident detection in `--split-by-language` mode.
+const UNDETERMINED_PARTITION: &str = "und";
+
+/// Shared across batches so every batch of an output file carries the same enum categories.
+static REJECT_REASON_DTYPE: Lazy<DataType> = Lazy::new(|| {
+    let categories = FrozenCategories::new(RowOutcome::REJECT_REASONS)
+        .expect("reject reasons must form valid enum categories");
+    DataType::from_frozen_categories(categories)
+});
+
+/// Detected languages for a row ranked by confidence; empty when nothing was detected.
+type Detection = Vec<(Language, f64)>;
+
+/// Why a row was kept or rejected. Rejected outcomes are written to the `reject_reason` column
+/// of the invalid output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowOutcome {
+    Kept,
+    Null,
+    Empty,
+    EmptyAfterClean,
+    Undetected,
+    WrongLanguage,
+    BelowThreshold,
+    Ambiguous,
+}
+
+impl RowOutcome {
+    const REJECT_REASONS: [&'static str; 7] = [
+        "null",
+        "empty",
+        "empty_after_clean",
+        "undetected",
+        "wrong_language",
+        "below_threshold",
+        "ambiguous",
+    ];
+
+    fn is_kept(self) -> bool {
+        self == Self::Kept
+    }
+
+    fn reject_reason(self) -> Option<&'static str> {
+        match self {
+            Self::Kept => None,
+            Self::Null => Some("null"),
+            Self::Empty => Some("empty"),
+            Self::EmptyAfterClean => Some("empty_after_clean"),
+            Self::Undetected => Some("undetected"),
+            Self::WrongLanguage => Some("wrong_language"),
+            Self::BelowThreshold => Some("below_threshold"),
+            Self::Ambiguous => Some("ambiguous"),
+        }
+    }
+}
 
 struct Summary<'a> {
     input_path: &'a Path,
     output_path: &'a Path,
     invalid_output_path: Option<&'a Path>,
-    total_rows: usize,
-    kept_rows: usize,
-    invalid_rows: usize,
-    target_langs: &'a HashSet<Language>,
+    stats: &'a RowStats,
+    languages: &'a LanguageSelection,
     cleaned: bool,
     threshold: f64,
 }
 
+/// One input file together with the paths its kept and rejected rows are written to.
+struct FileJob {
+    input_path: PathBuf,
+    output_path: PathBuf,
+    invalid_output_path: Option<PathBuf>,
+    /// Directory the kept rows are re-sharded into, with sharding limits but without `--coalesce`.
+    shard_dir: Option<PathBuf>,
+}
+
 pub fn process_input(
     cli: &Cli,
-    target_langs: &HashSet<Language>,
+    languages: &LanguageSelection,
     detector: &Arc<LanguageDetector>,
 ) -> Result<()> {
-    match (&cli.input, &cli.input_dir) {
-        (Some(input_path), None) => {
-            info!("processing input path '{}'", input_path.display());
-            process_input_path(
-                input_path,
-                &cli.output,
-                cli.output_invalid.as_deref(),
-                cli,
-                target_langs,
-                detector,
-            )
+    let output = cli
+        .split_by_language
+        .as_deref()
+        .or(cli.output.as_deref())
+        .expect("clap requires --output or --split-by-language unless a subcommand is given");
+    if cli.coalesce && cli.file_jobs.get() > 1 {
+        return Err(anyhow!(
+            "--coalesce writes the inputs one after another into shared parts and cannot be \
+             combined with --file-jobs greater than 1"
+        ));
+    }
+    let started = Instant::now();
+    let mut jobs = plan_jobs(cli, output)?;
+    ensure_nested_outputs(&jobs, cli)?;
+    let planned = jobs.len();
+    if cli.resume {
+        skip_completed_jobs(&mut jobs, cli);
+    }
+    let coalesced = match shard_limits(cli) {
+        Some(limits) if cli.coalesce => Some(Mutex::new(ShardedSink::new(
+            output,
+            coalesced_format(cli),
+            write_options(cli)?,
+            limits,
+        ))),
+        _ => None,
+    };
+    let files = run_jobs(&jobs, cli, languages, detector, coalesced.as_ref())?;
+    if let Some(coalesced) = coalesced {
+        coalesced
+            .into_inner()
+            .expect("coalesced sink lock poisoned")
+            .finish()?;
+    }
+
+    if let Some(report_path) = cli.report.as_deref() {
+        let report = RunReport::new(
+            Settings::new(cli, languages),
+            files,
+            planned - jobs.len(),
+            started.elapsed(),
+        );
+        report.write(report_path)?;
+        info!("wrote run report to '{}'", report_path.display());
+    }
+
+    Ok(())
+}
+
+/// Filter standard input line by line for `--stdin`. Each line is one row; kept lines go to
+/// standard output and, with `--output-invalid`, rejected lines go to that file. With `--clean`
+/// or normalization the processed text is written instead of the original line, unless
+/// `--keep-original` is set.
+pub fn process_lines(
+    cli: &Cli,
+    languages: &LanguageSelection,
+    detector: &Arc<LanguageDetector>,
+) -> Result<()> {
+    let invalid_output_path = cli.output_invalid.as_deref();
+    if let Some(invalid_output_path) = invalid_output_path {
+        ensure_file_output_path(invalid_output_path)?;
+    }
+    let mut invalid_sink = invalid_output_path.map(LineSink::create).transpose()?;
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut stdin = io::stdin().lock();
+    let mut stats = RowStats::default();
+    let mut lossy_lines = 0;
+
+    loop {
+        let batch = read_lines(&mut stdin, cli.batch_rows.get(), &mut lossy_lines)
+            .context("Failed to read standard input")?;
+        if batch.is_empty() {
+            break;
         }
-        (None, Some(input_dir)) => {
-            info!("processing input directory '{}'", input_dir.display());
-            process_directory(
-                input_dir,
-                &cli.output,
-                cli.output_invalid.as_deref(),
+
+        let column = StringChunked::from_iter_values(
+            cli.column.as_str().into(),
+            batch.iter().map(String::as_str),
+        );
+        let processed = process_column(&column, cli, &mut stats);
+        let detections = detect_languages(&processed, detection_candidates(cli), detector);
+        let outcomes = build_mask(
+            &column,
+            &processed,
+            &detections,
+            cli.keep_empty,
+            |detection| classify_detection(detection, languages, cli.min_margin),
+        );
+        record_rows(&mut stats, &outcomes, &detections);
+
+        for ((outcome, line), cleaned) in outcomes.iter().zip(&batch).zip(&processed) {
+            let text = match cleaned {
+                Some(cleaned) if !cli.keep_original => cleaned,
+                _ => line,
+            };
+            if outcome.is_kept() {
+                if let Err(err) = writeln!(stdout, "{text}") {
+                    return stdout_closed(err);
+                }
+            } else if let Some(invalid_sink) = invalid_sink.as_mut() {
+                invalid_sink.write_line(text)?;
+            }
+        }
+    }
+
+    if lossy_lines > 0 {
+        warn!("replaced invalid UTF-8 in {lossy_lines} line(s) of standard input");
+    }
+    if let Err(err) = stdout.flush() {
+        return stdout_closed(err);
+    }
+    if let Some(invalid_sink) = invalid_sink {
+        invalid_sink.finish()?;
+    }
+    print_summary(Summary {
+        input_path: Path::new("<stdin>"),
+        output_path: Path::new("<stdout>"),
+        invalid_output_path,
+        stats: &stats,
+        languages,
+        cleaned: cli.clean,
+        threshold: cli.threshold,
+    });
+
+    Ok(())
+}
+
+/// Read up to `limit` lines without their `\n` or `\r\n` terminator. Bytes that are not valid
+/// UTF-8 are replaced with U+FFFD and the affected lines are counted into `lossy_lines`.
+fn read_lines(
+    input: &mut impl BufRead,
+    limit: usize,
+    lossy_lines: &mut usize,
+) -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    while lines.len() < limit && input.read_until(b'\n', &mut line)? > 0 {
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        lines.push(match String::from_utf8(mem::take(&mut line)) {
+            Ok(line) => line,
+            Err(err) => {
+                *lossy_lines += 1;
+                String::from_utf8_lossy(err.as_bytes()).into_owned()
+            }
+        });
+    }
+    Ok(lines)
+}
+
+/// A closed standard output (e.g. piping into `head`) ends line mode quietly.
+fn stdout_closed(err: io::Error) -> Result<()> {
+    if err.kind() == ErrorKind::BrokenPipe {
+        Ok(())
+    } else {
+        Err(err).context("Failed to write to standard output")
+    }
+}
+
+/// Process every job, returning their reports in job order. With `--coalesce`, kept rows of every
+/// job go to the shared `coalesced` sink.
+fn run_jobs(
+    jobs: &[FileJob],
+    cli: &Cli,
+    languages: &LanguageSelection,
+    detector: &Arc<LanguageDetector>,
+    coalesced: Option<&Mutex<ShardedSink>>,
+) -> Result<Vec<FileReport>> {
+    let workers = cli.file_jobs.get().min(jobs.len());
+
+    if workers <= 1 {
+        return jobs
+            .iter()
+            .map(|job| process_job(job, cli, languages, detector, coalesced))
+            .collect();
+    }
+
+    info!(
+        "processing {} file(s) with {} concurrent jobs",
+        jobs.len(),
+        workers
+    );
+    let next_job = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let mut files: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| -> Result<Vec<(usize, FileReport)>> {
+                    let mut files = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            break;
+                        };
+                        match process_job(job, cli, languages, detector, coalesced) {
+                            Ok(file) => files.push((index, file)),
+                            Err(err) => {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Ok(files)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("file job thread panicked"))
+            .collect::<Result<Vec<_>>>()
+    })?
+    .into_iter()
+    .flatten()
+    .collect();
+
+    files.sort_by_key(|(index, _)| *index);
+    Ok(files.into_iter().map(|(_, file)| file).collect())
+}
+
+fn skip_completed_jobs(jobs: &mut Vec<FileJob>, cli: &Cli) {
+    let planned = jobs.len();
+    let complete = |path: &Path| is_complete_output(path, output_format(cli, path));
+    jobs.retain(|job| {
+        let completed =
+            complete(&job.output_path) && job.invalid_output_path.as_deref().is_none_or(complete);
+        if completed {
+            info!(
+                "skipping '{}': output '{}' is already complete",
+                job.input_path.display(),
+                job.output_path.display()
+            );
+        }
+        !completed
+    });
+
+    info!(
+        "resuming: {} of {} file(s) already complete, {} left to process",
+        planned - jobs.len(),
+        planned,
+        jobs.len()
+    );
+}
+
+fn process_job(
+    job: &FileJob,
+    cli: &Cli,
+    languages: &LanguageSelection,
+    detector: &Arc<LanguageDetector>,
+    coalesced: Option<&Mutex<ShardedSink>>,
+) -> Result<FileReport> {
+    match cli.split_by_language.as_deref() {
+        Some(split_dir) => {
+            let relative_path = job
+                .output_path
+                .strip_prefix(split_dir)
+                .expect("split jobs are planned under the split directory");
+            split_file(
+                &job.input_path,
+                split_dir,
+                relative_path,
                 cli,
-                target_langs,
+                languages,
                 detector,
             )
         }
-        _ => unreachable!("clap enforces that exactly one input source is provided"),
+        None => process_file(job, cli, languages, detector, coalesced),
+    }
+}
+
+/// Resolve every `--input`/`--input-dir` value into per-file jobs. A single plain path keeps the
+/// file-to-file or directory-to-directory behaviour; several inputs, glob patterns,
+/// `--split-by-language`, or sharded output always write into the output directories.
+fn plan_jobs(cli: &Cli, output: &Path) -> Result<Vec<FileJob>> {
+    let inputs: Vec<&Path> = cli
+        .input
+        .iter()
+        .chain(&cli.input_dir)
+        .map(PathBuf::as_path)
+        .collect();
+    let invalid_output = cli.output_invalid.as_deref();
+
+    let mut jobs = match inputs.as_slice() {
+        [input_path]
+            if !is_glob_pattern(input_path)
+                && cli.split_by_language.is_none()
+                && shard_limits(cli).is_none() =>
+        {
+            info!("processing input path '{}'", input_path.display());
+            plan_input_path(input_path, output, invalid_output, cli)?
+        }
+        _ => {
+            info!("processing {} input path(s) or pattern(s)", inputs.len());
+            let mut jobs = Vec::new();
+            for input in inputs {
+                for input_path in expand_input(input)? {
+                    jobs.extend(plan_input_into_directory(
+                        &input_path,
+                        output,
+                        invalid_output,
+                        cli,
+                    )?);
+                }
+            }
+            jobs
+        }
+    };
+
+    // A single input is re-sharded straight into the output directory; several inputs each get a
+    // part directory named after them so that their part files do not collide.
+    if shard_limits(cli).is_some() && !cli.coalesce {
+        let single = jobs.len() == 1;
+        for job in &mut jobs {
+            job.shard_dir = Some(if single {
+                output.to_path_buf()
+            } else {
+                job.output_path.with_extension("")
+            });
+        }
     }
+
+    ensure_unique_outputs(&jobs, cli)?;
+    Ok(jobs)
 }
 
-fn process_input_path(
+fn plan_input_path(
     input_path: &Path,
     output_path: &Path,
     invalid_output_path: Option<&Path>,
     cli: &Cli,
-    target_langs: &HashSet<Language>,
-    detector: &Arc<LanguageDetector>,
-) -> Result<()> {
-    let metadata = fs::metadata(input_path).with_context(|| {
-        format!(
-            "Input path '{}' does not exist or is inaccessible",
-            input_path.display()
-        )
-    })?;
+) -> Result<Vec<FileJob>> {
+    let metadata = input_metadata(input_path)?;
 
     if metadata.is_dir() {
-        process_directory(
-            input_path,
-            output_path,
-            invalid_output_path,
-            cli,
-            target_langs,
-            detector,
-        )
+        plan_directory(input_path, output_path, invalid_output_path, cli)
     } else if metadata.is_file() {
-        process_file(
-            input_path,
-            output_path,
+        input_format(cli, input_path)?;
+        Ok(vec![FileJob {
+            input_path: input_path.to_path_buf(),
+            output_path: output_path.to_path_buf(),
+            invalid_output_path: invalid_output_path.map(Path::to_path_buf),
+            shard_dir: None,
+        }])
+    } else {
+        Err(unsupported_input(input_path))
+    }
+}
+
+fn plan_input_into_directory(
+    input_path: &Path,
+    output_dir: &Path,
+    invalid_output_dir: Option<&Path>,
+    cli: &Cli,
+) -> Result<Vec<FileJob>> {
+    let metadata = input_metadata(input_path)?;
+
+    if metadata.is_dir() {
+        plan_directory(input_path, output_dir, invalid_output_dir, cli)
+    } else if metadata.is_file() {
+        input_format(cli, input_path)?;
+        ensure_output_directory(output_dir)?;
+        let base_dir = input_path.parent().unwrap_or(Path::new(""));
+        let invalid_output_path = invalid_output_dir
+            .map(|invalid_output_dir| {
+                ensure_output_directory(invalid_output_dir)?;
+                output_path_for_file(invalid_output_dir, base_dir, input_path, cli)
+            })
+            .transpose()?;
+        Ok(vec![FileJob {
+            input_path: input_path.to_path_buf(),
+            output_path: output_path_for_file(output_dir, base_dir, input_path, cli)?,
             invalid_output_path,
-            cli,
-            target_langs,
-            detector,
-        )
+            shard_dir: None,
+        }])
     } else {
-        Err(anyhow!(
-            "Input path '{}' must be a Parquet file or a directory containing Parquet files",
-            input_path.display()
-        ))
+        Err(unsupported_input(input_path))
     }
 }
 
-fn process_directory(
+fn plan_directory(
     input_dir: &Path,
     output_dir: &Path,
     invalid_output_dir: Option<&Path>,
     cli: &Cli,
-    target_langs: &HashSet<Language>,
-    detector: &Arc<LanguageDetector>,
-) -> Result<()> {
+) -> Result<Vec<FileJob>> {
     ensure_output_directory(output_dir)?;
     if let Some(invalid_output_dir) = invalid_output_dir {
         ensure_output_directory(invalid_output_dir)?;
     }
 
-    let files = collect_parquet_files(input_dir)?;
+    let files = collect_input_files(input_dir, cli)?;
     info!(
-        "found {} Parquet file(s) under '{}'",
+        "found {} input file(s) under '{}'",
         files.len(),
         input_dir.display()
     );
-    for input_path in files {
-        let output_path = output_path_for_file(output_dir, &input_path)?;
-        let invalid_output_path = invalid_output_dir
-            .map(|invalid_output_dir| output_path_for_file(invalid_output_dir, &input_path))
-            .transpose()?;
-        process_file(
-            &input_path,
-            &output_path,
-            invalid_output_path.as_deref(),
-            cli,
-            target_langs,
-            detector,
-        )?;
+    files
+        .into_iter()
+        .map(|input_path| {
+            let output_path = output_path_for_file(output_dir, input_dir, &input_path, cli)?;
+            let invalid_output_path = invalid_output_dir
+                .map(|invalid_output_dir| {
+                    output_path_for_file(invalid_output_dir, input_dir, &input_path, cli)
+                })
+                .transpose()?;
+            Ok(FileJob {
+                input_path,
+                output_path,
+                invalid_output_path,
+                shard_dir: None,
+            })
+        })
+        .collect()
+}
+
+fn input_metadata(input_path: &Path) -> Result<fs::Metadata> {
+    fs::metadata(input_path).with_context(|| {
+        format!(
+            "Input path '{}' does not exist or is inaccessible",
+            input_path.display()
+        )
+    })
+}
+
+fn unsupported_input(input_path: &Path) -> anyhow::Error {
+    anyhow!(
+        "Input path '{}' must be a file or a directory containing input files",
+        input_path.display()
+    )
+}
+
+/// Whether `input` is expanded as a glob: it holds `*`, `?`, or `[` and does not name an existing
+/// path, so files such as `clip[1].parquet` are still read literally.
+fn is_glob_pattern(input: &Path) -> bool {
+    input.to_string_lossy().contains(['*', '?', '[']) && !input.exists()
+}
+
+fn expand_input(input: &Path) -> Result<Vec<PathBuf>> {
+    if !is_glob_pattern(input) {
+        return Ok(vec![input.to_path_buf()]);
+    }
+
+    let pattern = input
+        .to_str()
+        .ok_or_else(|| anyhow!("Input pattern '{:?}' is not valid UTF-8", input))?;
+    let mut paths = glob::glob(pattern)
+        .with_context(|| format!("Invalid input pattern '{pattern}'"))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to expand input pattern '{pattern}'"))?;
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(anyhow!("No files match input pattern '{pattern}'"));
+    }
+
+    Ok(paths)
+}
+
+/// Reject jobs that would write to the same file or part directory. With `--coalesce` the kept
+/// rows of every job share one sink, so only the invalid outputs are checked.
+fn ensure_unique_outputs(jobs: &[FileJob], cli: &Cli) -> Result<()> {
+    let mut claimed: HashMap<&Path, &Path> = HashMap::new();
+    let outputs = jobs.iter().flat_map(|job| {
+        let kept = (!cli.coalesce).then(|| job.shard_dir.as_deref().unwrap_or(&job.output_path));
+        kept.into_iter()
+            .chain(job.invalid_output_path.as_deref())
+            .map(move |output_path| (output_path, job.input_path.as_path()))
+    });
+
+    for (output_path, input_path) in outputs {
+        if let Some(previous) = claimed.insert(output_path, input_path) {
+            return Err(anyhow!(
+                "Inputs '{}' and '{}' would both be written to '{}'",
+                previous.display(),
+                input_path.display(),
+                output_path.display()
+            ));
+        }
     }
 
     Ok(())
@@ -154,54 +619,176 @@ fn ensure_output_directory(output_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-fn collect_parquet_files(input_dir: &Path) -> Result<Vec<PathBuf>> {
-    let mut files: Vec<PathBuf> = fs::read_dir(input_dir)
-        .with_context(|| format!("Failed to read input directory '{}'", input_dir.display()))?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-            if entry.file_type().ok()?.is_file() && is_parquet_file(&path) {
-                Some(path)
-            } else {
-                None
-            }
-        })
-        .collect();
+/// Collect the input files in `input_dir`: every regular file when `--input-format` is given,
+/// otherwise only Parquet files, so that stray side files such as a `manifest.csv` are skipped.
+fn collect_input_files(input_dir: &Path, cli: &Cli) -> Result<Vec<PathBuf>> {
+    let max_depth = if cli.recursive { usize::MAX } else { 1 };
+    let mut files = Vec::new();
+    for entry in WalkDir::new(input_dir).min_depth(1).max_depth(max_depth) {
+        let entry = entry
+            .with_context(|| format!("Failed to read input directory '{}'", input_dir.display()))?;
+        if entry.file_type().is_file()
+            && (cli.input_format.is_some()
+                || FileFormat::from_path(entry.path()) == Some(FileFormat::Parquet))
+        {
+            files.push(entry.into_path());
+        }
+    }
 
     files.sort();
 
     if files.is_empty() {
-        return Err(anyhow!(
-            "No Parquet files found in input directory '{}'",
-            input_dir.display()
-        ));
+        return Err(match cli.input_format {
+            Some(_) => anyhow!(
+                "No input files found in input directory '{}'",
+                input_dir.display()
+            ),
+            None => anyhow!(
+                "No parquet input files found in input directory '{}'; pass --input-format to \
+                 read files of another format",
+                input_dir.display()
+            ),
+        });
     }
 
     Ok(files)
 }
 
-fn is_parquet_file(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.eq_ignore_ascii_case("parquet"))
-        .unwrap_or(false)
+/// Format of an input file: `--input-format` when given, otherwise implied by its extension.
+fn input_format(cli: &Cli, input_path: &Path) -> Result<FileFormat> {
+    cli.input_format
+        .or_else(|| FileFormat::from_path(input_path))
+        .ok_or_else(|| {
+            anyhow!(
+                "Cannot tell the format of input '{}' from its extension; pass --input-format",
+                input_path.display()
+            )
+        })
+}
+
+/// Format of an output file: `--output-format` when given, otherwise implied by its extension,
+/// falling back to Parquet.
+fn output_format(cli: &Cli, output_path: &Path) -> FileFormat {
+    cli.output_format
+        .or_else(|| FileFormat::from_path(output_path))
+        .unwrap_or(FileFormat::Parquet)
+}
+
+/// Format of the shared parts written with `--coalesce`: `--output-format`, falling back to
+/// Parquet.
+fn coalesced_format(cli: &Cli) -> FileFormat {
+    cli.output_format.unwrap_or(FileFormat::Parquet)
+}
+
+/// Reject `--top-k` up front when any kept, rejected, or per-language output is CSV or TSV, which
+/// cannot hold its list column, instead of failing on the first write.
+fn ensure_nested_outputs(jobs: &[FileJob], cli: &Cli) -> Result<()> {
+    if cli.top_k.is_none() {
+        return Ok(());
+    }
+
+    for job in jobs {
+        let kept = if cli.coalesce {
+            (
+                coalesced_format(cli),
+                cli.output.as_deref().unwrap_or(&job.output_path),
+            )
+        } else {
+            (
+                output_format(cli, &job.output_path),
+                job.output_path.as_path(),
+            )
+        };
+        let invalid = job
+            .invalid_output_path
+            .as_deref()
+            .map(|path| (output_format(cli, path), path));
+        for (format, path) in iter::once(kept).chain(invalid) {
+            if !format.supports_nested() {
+                return Err(anyhow!(
+                    "--top-k adds a list column, which {} output '{}' cannot hold; \
+                     pass --output-format parquet, ndjson, or ipc",
+                    format,
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    Ok(())
 }
 
-fn output_path_for_file(output_dir: &Path, input_path: &Path) -> Result<PathBuf> {
-    let file_name = input_path
-        .file_name()
+/// Mirror the location of `input_path` relative to `input_dir` under `output_dir`, switching the
+/// extension to match `--output-format` when given. Intermediate directories are created when the
+/// output is written.
+fn output_path_for_file(
+    output_dir: &Path,
+    input_dir: &Path,
+    input_path: &Path,
+    cli: &Cli,
+) -> Result<PathBuf> {
+    let relative_path = input_path
+        .strip_prefix(input_dir)
+        .ok()
+        .filter(|relative_path| relative_path.file_name().is_some())
         .ok_or_else(|| anyhow!("Invalid file name for '{:?}'", input_path))?;
-    Ok(output_dir.join(file_name))
+
+    let output_path = output_dir.join(relative_path);
+    Ok(match cli.output_format {
+        Some(format) => output_path.with_extension(format.extension()),
+        None => output_path,
+    })
+}
+
+/// Destination of the kept rows of one input file.
+enum KeptSink<'a> {
+    File(OutputSink),
+    Shards(ShardedSink),
+    /// Shared by every input with `--coalesce` and finished once all files are processed.
+    Coalesced(&'a Mutex<ShardedSink>),
+}
+
+impl KeptSink<'_> {
+    fn write(&mut self, batch: &DataFrame) -> Result<()> {
+        match self {
+            Self::File(sink) => sink.write(batch),
+            Self::Shards(sink) => sink.write(batch),
+            Self::Coalesced(sink) => sink
+                .lock()
+                .expect("coalesced sink lock poisoned")
+                .write(batch),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::File(sink) => sink.finish(),
+            Self::Shards(sink) => sink.finish(),
+            Self::Coalesced(_) => Ok(()),
+        }
+    }
+}
+
+/// Part-file limits when kept rows are re-sharded, or `None` for one output per input.
+fn shard_limits(cli: &Cli) -> Option<ShardLimits> {
+    let limits = ShardLimits {
+        max_rows: cli.max_rows_per_file.map(NonZeroUsize::get),
+        max_bytes: cli.max_bytes_per_file.map(NonZeroUsize::get),
+    };
+    (limits.max_rows.is_some() || limits.max_bytes.is_some() || cli.coalesce).then_some(limits)
 }
 
 fn process_file(
-    input_path: &Path,
-    output_path: &Path,
-    invalid_output_path: Option<&Path>,
+    job: &FileJob,
     cli: &Cli,
-    target_langs: &HashSet<Language>,
+    languages: &LanguageSelection,
     detector: &Arc<LanguageDetector>,
-) -> Result<()> {
+    coalesced: Option<&Mutex<ShardedSink>>,
+) -> Result<FileReport> {
+    let started = Instant::now();
+    let input_path = job.input_path.as_path();
+    let output_path = job.output_path.as_path();
+    let invalid_output_path = job.invalid_output_path.as_deref();
     ensure_file_output_path(output_path)?;
     if let Some(invalid_output_path) = invalid_output_path {
         ensure_file_output_path(invalid_output_path)?;
@@ -215,110 +802,450 @@ fn process_file(
         }
     }
 
+    let options = write_options(cli)?;
+    let format = output_format(cli, output_path);
+    let (mut sink, kept_output) = match (coalesced, shard_limits(cli), &job.shard_dir) {
+        (Some(coalesced), _, _) => (
+            KeptSink::Coalesced(coalesced),
+            cli.output.clone().unwrap_or_default(),
+        ),
+        (None, Some(limits), Some(shard_dir)) => (
+            KeptSink::Shards(ShardedSink::new(shard_dir, format, options, limits)),
+            shard_dir.clone(),
+        ),
+        _ => (
+            KeptSink::File(OutputSink::new(output_path, format, options)),
+            output_path.to_path_buf(),
+        ),
+    };
     info!(
         "filtering '{}' into '{}'",
         input_path.display(),
-        output_path.display()
+        kept_output.display()
     );
-    let df = read_parquet(input_path)?;
-    let processed = process_column(&df, cli)?;
-    let mask = build_mask(
-        &processed,
-        cli.keep_empty,
-        cli.threshold,
-        target_langs,
-        detector,
-    );
-    let mut filtered = filter_dataframe(&df, &mask)?;
-    let invalid_mask = invert_mask(&mask);
-    let mut invalid = invalid_output_path
-        .map(|_| filter_dataframe(&df, &invalid_mask))
-        .transpose()?;
+    let mut invalid_sink =
+        invalid_output_path.map(|path| OutputSink::new(path, output_format(cli, path), options));
+    let mut stats = RowStats::default();
+    let batches = InputBatches::open(
+        input_path,
+        input_format(cli, input_path)?,
+        cli.batch_rows.get(),
+    )?;
+    for batch in batches {
+        let (filtered, invalid) = filter_batch(
+            &batch?,
+            invalid_sink.is_some(),
+            cli,
+            languages,
+            detector,
+            &mut stats,
+        )?;
 
-    if cli.clean {
-        replace_text_column(&mut filtered, &cli.column, &processed, &mask)?;
-        if let Some(invalid) = invalid.as_mut() {
-            replace_text_column(invalid, &cli.column, &processed, &invalid_mask)?;
+        sink.write(&filtered)?;
+        if let Some((invalid_sink, invalid)) = invalid_sink.as_mut().zip(invalid) {
+            invalid_sink.write(&invalid)?;
         }
     }
 
-    write_parquet(output_path, &mut filtered)?;
-    if let Some((invalid_output_path, invalid)) = invalid_output_path.zip(invalid.as_mut()) {
-        write_parquet(invalid_output_path, invalid)?;
+    sink.finish()?;
+    if let Some(invalid_sink) = invalid_sink {
+        invalid_sink.finish()?;
     }
     print_summary(Summary {
         input_path,
-        output_path,
+        output_path: &kept_output,
         invalid_output_path,
-        total_rows: mask.len(),
-        kept_rows: filtered.height(),
-        invalid_rows: invalid.as_ref().map_or(0, DataFrame::height),
-        target_langs,
+        stats: &stats,
+        languages,
         cleaned: cli.clean,
         threshold: cli.threshold,
     });
 
-    Ok(())
+    Ok(FileReport {
+        input: input_path.to_path_buf(),
+        output: kept_output,
+        invalid_output: invalid_output_path.map(Path::to_path_buf),
+        elapsed_secs: started.elapsed().as_secs_f64(),
+        stats,
+    })
+}
+
+fn write_options(cli: &Cli) -> Result<WriteOptions> {
+    WriteOptions::new(
+        cli.compression,
+        cli.compression_level,
+        cli.statistics,
+        cli.row_group_size,
+    )
 }
 
-fn read_parquet(input_path: &Path) -> Result<DataFrame> {
-    let file = File::open(input_path)?;
-    let reader = ParquetReader::new(file);
-    Ok(reader.finish()?)
+/// Route every row of `input_path` to `split_dir/<iso>/<relative_path>` by its top detected
+/// language. Rows without a confident detection go to the `und` partition.
+fn split_file(
+    input_path: &Path,
+    split_dir: &Path,
+    relative_path: &Path,
+    cli: &Cli,
+    languages: &LanguageSelection,
+    detector: &Arc<LanguageDetector>,
+) -> Result<FileReport> {
+    let started = Instant::now();
+    info!(
+        "splitting '{}' by detected language into '{}'",
+        input_path.display(),
+        split_dir.display()
+    );
+    let options = write_options(cli)?;
+    let format = output_format(cli, relative_path);
+    let mut sinks: BTreeMap<String, OutputSink> = BTreeMap::new();
+    let mut stats = RowStats::default();
+    let batches = InputBatches::open(
+        input_path,
+        input_format(cli, input_path)?,
+        cli.batch_rows.get(),
+    )?;
+    for batch in batches {
+        for (partition, rows) in split_batch(&batch?, cli, detector, &mut stats)? {
+            sinks
+                .entry(partition)
+                .or_insert_with_key(|partition| {
+                    let path = split_dir.join(partition).join(relative_path);
+                    OutputSink::new(&path, format, options)
+                })
+                .write(&rows)?;
+        }
+    }
+
+    let partitions: Vec<String> = sinks.keys().cloned().collect();
+    for sink in sinks.into_values() {
+        sink.finish()?;
+    }
+    info!(
+        "Split {} rows into {} partition(s) {:?} (candidates = {}, cleaned = {}, threshold = {}) [{} -> {}]",
+        stats.total_rows,
+        partitions.len(),
+        partitions,
+        languages.describe_candidates(),
+        cli.clean,
+        cli.threshold,
+        input_path.display(),
+        split_dir.display()
+    );
+    print_repairs(&stats, input_path);
+
+    Ok(FileReport {
+        input: input_path.to_path_buf(),
+        output: split_dir.to_path_buf(),
+        invalid_output: None,
+        elapsed_secs: started.elapsed().as_secs_f64(),
+        stats,
+    })
+}
+
+/// Partition one batch by top detected language, keyed by ISO 639-1 code or `und`. The `und`
+/// partition carries the `reject_reason` column. Every row is counted into `stats`.
+fn split_batch(
+    df: &DataFrame,
+    cli: &Cli,
+    detector: &Arc<LanguageDetector>,
+    stats: &mut RowStats,
+) -> Result<Vec<(String, DataFrame)>> {
+    let column = text_column(df, &cli.column)?;
+    let processed = process_column(column, cli, stats);
+    let detections = detect_languages(&processed, detection_candidates(cli), detector);
+    let outcomes = build_mask(column, &processed, &detections, false, |detection| {
+        classify_partition(detection, cli.threshold, cli.min_margin)
+    });
+    record_rows(stats, &outcomes, &detections);
+
+    let partitions: Vec<String> = outcomes
+        .iter()
+        .zip(&detections)
+        .map(|(outcome, detection)| match (outcome, detection.first()) {
+            (RowOutcome::Kept, Some((language, _))) => language.iso_code_639_1().to_string(),
+            _ => UNDETERMINED_PARTITION.to_string(),
+        })
+        .collect();
+    let mut names: Vec<&String> = partitions.iter().collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let mask: Vec<bool> = partitions
+                .iter()
+                .map(|partition| partition == name)
+                .collect();
+            let mut rows = select_rows(df, &mask, &processed, &detections, cli)?;
+            if name == UNDETERMINED_PARTITION {
+                append_reject_reason_column(&mut rows, &outcomes, &mask)?;
+            }
+            Ok((name.clone(), rows))
+        })
+        .collect()
+}
+
+/// Filter one batch of rows, returning the kept rows and, when `with_invalid` is set, the
+/// rejected rows. Every row is counted into `stats`.
+fn filter_batch(
+    df: &DataFrame,
+    with_invalid: bool,
+    cli: &Cli,
+    languages: &LanguageSelection,
+    detector: &Arc<LanguageDetector>,
+    stats: &mut RowStats,
+) -> Result<(DataFrame, Option<DataFrame>)> {
+    let column = text_column(df, &cli.column)?;
+    let processed = process_column(column, cli, stats);
+    let detections = detect_languages(&processed, detection_candidates(cli), detector);
+    let outcomes = if cli.annotate && !with_invalid {
+        vec![RowOutcome::Kept; detections.len()]
+    } else {
+        build_mask(
+            column,
+            &processed,
+            &detections,
+            cli.keep_empty,
+            |detection| classify_detection(detection, languages, cli.min_margin),
+        )
+    };
+    record_rows(stats, &outcomes, &detections);
+
+    let mask: Vec<bool> = outcomes.iter().copied().map(RowOutcome::is_kept).collect();
+    let filtered = select_rows(df, &mask, &processed, &detections, cli)?;
+    let invalid = with_invalid
+        .then(|| {
+            let invalid_mask = invert_mask(&mask);
+            let mut invalid = select_rows(df, &invalid_mask, &processed, &detections, cli)?;
+            append_reject_reason_column(&mut invalid, &outcomes, &invalid_mask)?;
+            Ok::<_, anyhow::Error>(invalid)
+        })
+        .transpose()?;
+
+    Ok((filtered, invalid))
+}
+
+/// Number of ranked languages kept per row: enough for `--top-k` and the `--min-margin` runner-up.
+fn detection_candidates(cli: &Cli) -> usize {
+    cli.top_k
+        .map_or(1, NonZeroUsize::get)
+        .max(if cli.min_margin.is_some() { 2 } else { 1 })
+}
+
+fn record_rows(stats: &mut RowStats, outcomes: &[RowOutcome], detections: &[Detection]) {
+    for (outcome, detection) in outcomes.iter().zip(detections) {
+        stats.record(outcome.reject_reason(), detection.first().copied());
+    }
+}
+
+/// Select the rows under `mask`, applying `--clean`, `--clean-column`, `--annotate`, and
+/// `--top-k` to them.
+fn select_rows(
+    df: &DataFrame,
+    mask: &[bool],
+    processed: &[Option<String>],
+    detections: &[Detection],
+    cli: &Cli,
+) -> Result<DataFrame> {
+    let mut rows = filter_dataframe(df, mask)?;
+
+    if let Some(column_name) = cleaned_column_name(cli) {
+        write_text_column(&mut rows, column_name, processed, mask)?;
+    }
+
+    if cli.annotate {
+        append_detection_columns(&mut rows, detections, mask)?;
+    }
+
+    if let Some(top_k) = cli.top_k {
+        append_candidates_column(&mut rows, detections, mask, top_k.get())?;
+    }
+
+    Ok(rows)
 }
 
-fn process_column(df: &DataFrame, cli: &Cli) -> Result<Vec<Option<String>>> {
-    let column = df
-        .column(&cli.column)
-        .with_context(|| format!("Column '{}' not found", &cli.column))?
+fn text_column<'a>(df: &'a DataFrame, column_name: &str) -> Result<&'a StringChunked> {
+    df.column(column_name)
+        .with_context(|| format!("Column '{}' not found", column_name))?
         .str()
-        .context("Target column is not String")?;
+        .context("Target column is not String")
+}
+
+/// Column receiving the processed text: the text column itself unless `--keep-original` or
+/// `--clean-column` preserves it, or `None` when the text is neither normalized nor cleaned.
+fn cleaned_column_name(cli: &Cli) -> Option<&str> {
+    let rewrites_text =
+        cli.clean || cli.fix_homoglyphs || !cli.strip.is_empty() || normalizer(cli).is_active();
+    if !rewrites_text {
+        return None;
+    }
+
+    match cli.clean_column.as_deref() {
+        Some(column_name) => Some(column_name),
+        None if cli.keep_original => None,
+        None => Some(&cli.column),
+    }
+}
+
+fn normalizer(cli: &Cli) -> Normalizer {
+    Normalizer {
+        form: cli.normalize,
+        apostrophe: cli.apostrophe,
+        dash: cli.dash,
+    }
+}
 
-    Ok(column
+/// Normalize, strip markup, repair homoglyphs, and clean every value of the text column as configured.
+/// Repaired homoglyphs are counted into `stats`.
+fn process_column(column: &StringChunked, cli: &Cli, stats: &mut RowStats) -> Vec<Option<String>> {
+    let normalizer = normalizer(cli);
+    column
         .into_iter()
         .map(|opt| {
             opt.map(|text| {
+                let mut text = if normalizer.is_active() {
+                    Cow::Owned(normalizer.normalize(text))
+                } else {
+                    Cow::Borrowed(text)
+                };
+                if !cli.strip.is_empty() {
+                    text = Cow::Owned(strip_markup(&text, &cli.strip));
+                }
+                if cli.fix_homoglyphs {
+                    let (fixed, repaired) = fix_homoglyphs(&text);
+                    stats.repaired_homoglyphs += repaired;
+                    text = Cow::Owned(fixed);
+                }
                 if cli.clean {
-                    clean_text(text)
+                    cli.clean_profile.clean(&text)
                 } else {
-                    text.to_string()
+                    text.into_owned()
                 }
             })
         })
-        .collect())
+        .collect()
 }
 
-fn build_mask(
+fn detect_languages(
     processed: &[Option<String>],
-    keep_empty: bool,
-    threshold: f64,
-    target_langs: &HashSet<Language>,
+    candidates: usize,
     detector: &Arc<LanguageDetector>,
-) -> Vec<bool> {
+) -> Vec<Detection> {
     processed
         .par_iter()
         .map(|opt_text| match opt_text {
-            None => keep_empty,
-            Some(text) if text.is_empty() => keep_empty,
-            Some(text) => matches_threshold(text, threshold, target_langs, detector),
+            Some(text) if !text.is_empty() => detect_language(text, candidates, detector),
+            _ => Detection::new(),
         })
         .collect()
 }
 
-fn matches_threshold(
-    text: &str,
-    threshold: f64,
-    target_langs: &HashSet<Language>,
-    detector: &Arc<LanguageDetector>,
-) -> bool {
+fn detect_language(text: &str, candidates: usize, detector: &Arc<LanguageDetector>) -> Detection {
     detector
         .compute_language_confidence_values(text)
         .into_iter()
-        .next()
-        .map(|(language, confidence)| target_langs.contains(&language) && confidence >= threshold)
+        .take_while(|(_, confidence)| *confidence > 0.0)
+        .take(candidates)
+        .collect()
+}
+
+fn build_mask(
+    original: &StringChunked,
+    processed: &[Option<String>],
+    detections: &[Detection],
+    keep_empty: bool,
+    classify: impl Fn(&Detection) -> RowOutcome,
+) -> Vec<RowOutcome> {
+    original
+        .into_iter()
+        .zip(processed)
+        .zip(detections)
+        .map(|((raw, opt_text), detection)| {
+            let outcome = match (raw, opt_text) {
+                (None, _) | (_, None) => RowOutcome::Null,
+                (Some(""), Some(_)) => RowOutcome::Empty,
+                (Some(_), Some(text)) if text.is_empty() => RowOutcome::EmptyAfterClean,
+                (Some(_), Some(_)) => classify(detection),
+            };
+
+            match outcome {
+                RowOutcome::Null | RowOutcome::Empty | RowOutcome::EmptyAfterClean
+                    if keep_empty =>
+                {
+                    RowOutcome::Kept
+                }
+                outcome => outcome,
+            }
+        })
+        .collect()
+}
+
+fn classify_detection(
+    detection: &Detection,
+    languages: &LanguageSelection,
+    min_margin: Option<f64>,
+) -> RowOutcome {
+    if languages.is_exclusion() {
+        return if matches_threshold(detection, &languages.excluded) {
+            RowOutcome::WrongLanguage
+        } else {
+            RowOutcome::Kept
+        };
+    }
+
+    let Some((language, confidence)) = detection.first() else {
+        return RowOutcome::Undetected;
+    };
+    match languages.targets.get(language) {
+        None => RowOutcome::WrongLanguage,
+        Some(threshold) if confidence < threshold => RowOutcome::BelowThreshold,
+        Some(_) if min_margin.is_some_and(|min_margin| is_ambiguous(detection, min_margin)) => {
+            RowOutcome::Ambiguous
+        }
+        Some(_) => RowOutcome::Kept,
+    }
+}
+
+/// In `--split-by-language` mode a row belongs to its top language when that language clears
+/// the threshold and, with `--min-margin`, is not ambiguous.
+fn classify_partition(
+    detection: &Detection,
+    threshold: f64,
+    min_margin: Option<f64>,
+) -> RowOutcome {
+    match detection.first() {
+        None => RowOutcome::Undetected,
+        Some((_, confidence)) if *confidence < threshold => RowOutcome::BelowThreshold,
+        Some(_) if min_margin.is_some_and(|min_margin| is_ambiguous(detection, min_margin)) => {
+            RowOutcome::Ambiguous
+        }
+        Some(_) => RowOutcome::Kept,
+    }
+}
+
+fn matches_threshold(detection: &Detection, thresholds: &HashMap<Language, f64>) -> bool {
+    detection
+        .first()
+        .and_then(|(language, confidence)| {
+            thresholds
+                .get(language)
+                .map(|threshold| *confidence >= *threshold)
+        })
         .unwrap_or(false)
 }
 
+/// A detection is ambiguous when the top language does not beat the runner-up by more than
+/// `min_margin`.
+fn is_ambiguous(detection: &Detection, min_margin: f64) -> bool {
+    let confidence_at = |rank: usize| {
+        detection
+            .get(rank)
+            .map_or(0.0, |(_, confidence)| *confidence)
+    };
+    confidence_at(0) - confidence_at(1) <= min_margin
+}
+
 fn filter_dataframe(df: &DataFrame, mask: &[bool]) -> Result<DataFrame> {
     let mask = BooleanChunked::from_slice("mask".into(), mask);
     Ok(df.filter(&mask)?)
@@ -328,23 +1255,108 @@ fn invert_mask(mask: &[bool]) -> Vec<bool> {
     mask.iter().map(|keep| !keep).collect()
 }
 
-fn replace_text_column(
+fn select_by_mask<T: Clone>(values: &[T], mask: &[bool]) -> Vec<T> {
+    values
+        .iter()
+        .zip(mask)
+        .filter(|(_, keep)| **keep)
+        .map(|(value, _)| value.clone())
+        .collect()
+}
+
+/// Replace or append the `column_name` column with the processed text of the rows under `mask`.
+fn write_text_column(
     filtered: &mut DataFrame,
     column_name: &str,
     processed: &[Option<String>],
     mask: &[bool],
 ) -> Result<()> {
-    let cleaned_filtered: Vec<Option<String>> = processed
-        .iter()
-        .cloned()
-        .zip(mask.iter())
-        .filter_map(|(value, keep)| if *keep { Some(value) } else { None })
-        .collect();
+    let cleaned_filtered = select_by_mask(processed, mask);
     let cleaned_series = Series::new(column_name.into(), cleaned_filtered);
     filtered.with_column(cleaned_series)?;
     Ok(())
 }
 
+fn append_detection_columns(
+    filtered: &mut DataFrame,
+    detections: &[Detection],
+    mask: &[bool],
+) -> Result<()> {
+    let selected = select_by_mask(detections, mask);
+    let languages: Vec<Option<String>> = selected
+        .iter()
+        .map(|detection| {
+            detection
+                .first()
+                .map(|(language, _)| language.iso_code_639_1().to_string())
+        })
+        .collect();
+    let confidences: Vec<Option<f64>> = selected
+        .iter()
+        .map(|detection| detection.first().map(|(_, confidence)| *confidence))
+        .collect();
+
+    filtered.with_column(Series::new(DETECTED_LANG_COLUMN.into(), languages))?;
+    filtered.with_column(Series::new(LANG_CONFIDENCE_COLUMN.into(), confidences))?;
+    Ok(())
+}
+
+fn append_candidates_column(
+    filtered: &mut DataFrame,
+    detections: &[Detection],
+    mask: &[bool],
+    top_k: usize,
+) -> Result<()> {
+    let selected = select_by_mask(detections, mask);
+    let candidate_dtype = DataType::Struct(vec![
+        Field::new("lang".into(), DataType::String),
+        Field::new("confidence".into(), DataType::Float64),
+    ]);
+    let mut builder = get_list_builder(
+        &candidate_dtype,
+        selected.len() * top_k,
+        selected.len(),
+        LANG_CANDIDATES_COLUMN.into(),
+    );
+
+    for detection in &selected {
+        let languages: Vec<String> = detection
+            .iter()
+            .take(top_k)
+            .map(|(language, _)| language.iso_code_639_1().to_string())
+            .collect();
+        let confidences: Vec<f64> = detection
+            .iter()
+            .take(top_k)
+            .map(|(_, confidence)| *confidence)
+            .collect();
+        let fields = [
+            Series::new("lang".into(), languages),
+            Series::new("confidence".into(), confidences),
+        ];
+        let row = StructChunked::from_series("candidate".into(), fields[0].len(), fields.iter())?;
+        builder.append_series(&row.into_series())?;
+    }
+
+    filtered.with_column(builder.finish().into_series())?;
+    Ok(())
+}
+
+fn append_reject_reason_column(
+    invalid: &mut DataFrame,
+    outcomes: &[RowOutcome],
+    mask: &[bool],
+) -> Result<()> {
+    let reasons: Vec<Option<&str>> = select_by_mask(outcomes, mask)
+        .into_iter()
+        .map(RowOutcome::reject_reason)
+        .collect();
+    let reasons = Series::new(REJECT_REASON_COLUMN.into(), reasons).cast(&REJECT_REASON_DTYPE)?;
+
+    invalid.with_column(reasons)?;
+    Ok(())
+}
+
 fn ensure_file_output_path(output_path: &Path) -> Result<()> {
     if output_path.exists() && output_path.is_dir() {
         return Err(anyhow!(
@@ -356,23 +1368,15 @@ fn ensure_file_output_path(output_path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn write_parquet(output_path: &Path, filtered: &mut DataFrame) -> Result<()> {
-    let mut out_file =
-        File::create(output_path).with_context(|| format!("Cannot create {:?}", output_path))?;
-    ParquetWriter::new(&mut out_file)
-        .with_compression(ParquetCompression::Zstd(None))
-        .finish(filtered)?;
-    Ok(())
-}
-
 fn print_summary(summary: Summary<'_>) {
     match summary.invalid_output_path {
         Some(invalid_output_path) => info!(
-            "Filtered {} rows -> {} rows kept, {} rows rejected (langs = {:?}, cleaned = {}, threshold = {}) [{} -> {}, invalid -> {}]",
-            summary.total_rows,
-            summary.kept_rows,
-            summary.invalid_rows,
-            summary.target_langs,
+            "Filtered {} rows -> {} rows kept, {} rows rejected (langs = {}, candidates = {}, cleaned = {}, threshold = {}) [{} -> {}, invalid -> {}]",
+            summary.stats.total_rows,
+            summary.stats.kept_rows,
+            summary.stats.rejected_rows,
+            summary.languages.describe_targets(),
+            summary.languages.describe_candidates(),
             summary.cleaned,
             summary.threshold,
             summary.input_path.display(),
@@ -380,14 +1384,26 @@ fn print_summary(summary: Summary<'_>) {
             invalid_output_path.display()
         ),
         None => info!(
-            "Filtered {} rows -> {} rows kept (langs = {:?}, cleaned = {}, threshold = {}) [{} -> {}]",
-            summary.total_rows,
-            summary.kept_rows,
-            summary.target_langs,
+            "Filtered {} rows -> {} rows kept (langs = {}, candidates = {}, cleaned = {}, threshold = {}) [{} -> {}]",
+            summary.stats.total_rows,
+            summary.stats.kept_rows,
+            summary.languages.describe_targets(),
+            summary.languages.describe_candidates(),
             summary.cleaned,
             summary.threshold,
             summary.input_path.display(),
             summary.output_path.display()
         ),
     }
+    print_repairs(summary.stats, summary.input_path);
+}
+
+fn print_repairs(stats: &RowStats, input_path: &Path) {
+    if stats.repaired_homoglyphs > 0 {
+        info!(
+            "Repaired {} Latin/Cyrillic homoglyph(s) in mixed-script words [{}]",
+            stats.repaired_homoglyphs,
+            input_path.display()
+        );
+    }
 }
@@ -0,0 +1,231 @@
+use crate::cli::Cli;
+use crate::detect::LanguageSelection;
+use anyhow::{Context, Result};
+use lingua::Language;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Number of equal-width buckets in the confidence histogram. Bucket `i` counts rows whose top
+/// detected confidence lies in `[i / 10, (i + 1) / 10)`; the last bucket also includes 1.0.
+const CONFIDENCE_BUCKETS: usize = 10;
+
+/// Machine-readable run summary written by `--report`.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub settings: Settings,
+    pub elapsed_secs: f64,
+    pub skipped_files: usize,
+    pub totals: RowStats,
+    pub files: Vec<FileReport>,
+}
+
+impl RunReport {
+    pub fn new(
+        settings: Settings,
+        files: Vec<FileReport>,
+        skipped_files: usize,
+        elapsed: Duration,
+    ) -> Self {
+        let mut totals = RowStats::default();
+        for file in &files {
+            totals.merge(&file.stats);
+        }
+
+        Self {
+            settings,
+            elapsed_secs: elapsed.as_secs_f64(),
+            skipped_files,
+            totals,
+            files,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize run report")?;
+        fs::write(path, json + "\n")
+            .with_context(|| format!("Failed to write run report to {:?}", path))
+    }
+}
+
+/// Filtering options the run was started with.
+#[derive(Debug, Serialize)]
+pub struct Settings {
+    pub column: String,
+    pub langs: BTreeMap<String, f64>,
+    pub exclude_langs: BTreeMap<String, f64>,
+    pub candidates: Option<Vec<String>>,
+    pub threshold: f64,
+    pub min_margin: Option<f64>,
+    pub keep_empty: bool,
+    pub clean: bool,
+    pub clean_profile: Option<String>,
+    pub normalize: Option<String>,
+    pub apostrophe: Option<char>,
+    pub dash: Option<char>,
+    pub strip: Vec<String>,
+    pub fix_homoglyphs: bool,
+    pub keep_original: bool,
+    pub clean_column: Option<String>,
+    pub annotate: bool,
+    pub top_k: Option<usize>,
+    pub batch_rows: usize,
+}
+
+impl Settings {
+    pub fn new(cli: &Cli, languages: &LanguageSelection) -> Self {
+        let codes = |thresholds: &HashMap<Language, f64>| {
+            thresholds
+                .iter()
+                .map(|(language, threshold)| (language.iso_code_639_1().to_string(), *threshold))
+                .collect()
+        };
+        let candidates = languages.candidates.as_ref().map(|candidates| {
+            let mut codes: Vec<String> = candidates
+                .iter()
+                .map(|language| language.iso_code_639_1().to_string())
+                .collect();
+            codes.sort();
+            codes
+        });
+
+        Self {
+            column: cli.column.clone(),
+            langs: codes(&languages.targets),
+            exclude_langs: codes(&languages.excluded),
+            candidates,
+            threshold: cli.threshold,
+            min_margin: cli.min_margin,
+            keep_empty: cli.keep_empty,
+            clean: cli.clean,
+            clean_profile: cli.clean.then(|| cli.clean_profile.name().to_string()),
+            normalize: cli.normalize.map(|form| form.to_string()),
+            apostrophe: cli.apostrophe,
+            dash: cli.dash,
+            strip: cli.strip.iter().map(ToString::to_string).collect(),
+            fix_homoglyphs: cli.fix_homoglyphs,
+            keep_original: cli.keep_original || cli.clean_column.is_some(),
+            clean_column: cli.clean_column.clone(),
+            annotate: cli.annotate,
+            top_k: cli.top_k.map(NonZeroUsize::get),
+            batch_rows: cli.batch_rows.get(),
+        }
+    }
+}
+
+/// Statistics for one input file and the paths its rows were written to.
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub invalid_output: Option<PathBuf>,
+    pub elapsed_secs: f64,
+    #[serde(flatten)]
+    pub stats: RowStats,
+}
+
+/// Row counts accumulated over batches and files.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RowStats {
+    pub total_rows: usize,
+    pub kept_rows: usize,
+    pub rejected_rows: usize,
+    pub reject_reasons: BTreeMap<&'static str, usize>,
+    /// Rows per top detected language (ISO 639-1 code), whether kept or rejected.
+    pub detected_languages: BTreeMap<String, usize>,
+    pub confidence_histogram: [usize; CONFIDENCE_BUCKETS],
+    /// Look-alike characters replaced by `--fix-homoglyphs`.
+    pub repaired_homoglyphs: usize,
+}
+
+impl RowStats {
+    /// Count one row given its reject reason (`None` when kept) and top detected language.
+    pub fn record(&mut self, reject_reason: Option<&'static str>, top: Option<(Language, f64)>) {
+        self.total_rows += 1;
+        match reject_reason {
+            Some(reason) => {
+                self.rejected_rows += 1;
+                *self.reject_reasons.entry(reason).or_default() += 1;
+            }
+            None => self.kept_rows += 1,
+        }
+
+        if let Some((language, confidence)) = top {
+            *self
+                .detected_languages
+                .entry(language.iso_code_639_1().to_string())
+                .or_default() += 1;
+            let bucket = (confidence * CONFIDENCE_BUCKETS as f64) as usize;
+            self.confidence_histogram[bucket.min(CONFIDENCE_BUCKETS - 1)] += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &RowStats) {
+        self.total_rows += other.total_rows;
+        self.kept_rows += other.kept_rows;
+        self.rejected_rows += other.rejected_rows;
+        self.repaired_homoglyphs += other.repaired_homoglyphs;
+        for (reason, rows) in &other.reject_reasons {
+            *self.reject_reasons.entry(reason).or_default() += rows;
+        }
+        for (language, rows) in &other.detected_languages {
+            *self.detected_languages.entry(language.clone()).or_default() += rows;
+        }
+        for (bucket, rows) in self
+            .confidence_histogram
+            .iter_mut()
+            .zip(other.confidence_histogram)
+        {
+            *bucket += rows;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_counts_outcomes_languages_and_confidence_buckets() {
+        let mut stats = RowStats::default();
+        stats.record(None, Some((Language::Ukrainian, 1.0)));
+        stats.record(Some("wrong_language"), Some((Language::English, 0.05)));
+        stats.record(Some("null"), None);
+
+        assert_eq!(stats.total_rows, 3);
+        assert_eq!(stats.kept_rows, 1);
+        assert_eq!(stats.rejected_rows, 2);
+        assert_eq!(stats.reject_reasons.get("wrong_language"), Some(&1));
+        assert_eq!(stats.reject_reasons.get("null"), Some(&1));
+        assert_eq!(stats.detected_languages.get("uk"), Some(&1));
+        assert_eq!(stats.detected_languages.get("en"), Some(&1));
+        assert_eq!(stats.confidence_histogram[0], 1);
+        assert_eq!(stats.confidence_histogram[CONFIDENCE_BUCKETS - 1], 1);
+    }
+
+    #[test]
+    fn merge_adds_every_counter() {
+        let mut first = RowStats::default();
+        first.record(None, Some((Language::Ukrainian, 0.95)));
+        let mut second = RowStats::default();
+        second.record(None, Some((Language::Ukrainian, 0.91)));
+        second.record(Some("undetected"), None);
+        second.repaired_homoglyphs = 3;
+
+        let mut totals = RowStats::default();
+        totals.merge(&first);
+        totals.merge(&second);
+
+        assert_eq!(totals.total_rows, 3);
+        assert_eq!(totals.kept_rows, 2);
+        assert_eq!(totals.reject_reasons.get("undetected"), Some(&1));
+        assert_eq!(totals.detected_languages.get("uk"), Some(&2));
+        assert_eq!(totals.confidence_histogram[9], 2);
+        assert_eq!(totals.repaired_homoglyphs, 3);
+    }
+}
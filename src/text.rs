@@ -1,26 +1,449 @@
+use crate::cli::{Markup, Normalization};
+use anyhow::{Context, Result, anyhow};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Deserialize;
+use std::{borrow::Cow, cmp::Ordering, fs, path::Path};
+use unicode_normalization::UnicodeNormalization;
 
-/// Remove all symbols except letters, spaces, and punctuation for Ukrainian, Russian, and English texts.
-pub fn clean_text(text: &str) -> String {
-    static WHITESPACE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"\s+").expect("whitespace regex must compile"));
-    static DROP_NON_L_P_SPACE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r#"[^ \p{L}\p{P}]"#).expect("cleanup regex must compile"));
+/// Characters used as apostrophes in Ukrainian text.
+const APOSTROPHES: [char; 7] = ['\'', '’', 'ʼ', '‘', '`', '´', '′'];
+/// Hyphen-minus plus the Unicode hyphens, dashes, and minus sign.
+const DASHES: [char; 8] = ['-', '‐', '‑', '‒', '–', '—', '―', '−'];
 
-    let spaced = WHITESPACE.replace_all(text, " ");
-    let cleaned = DROP_NON_L_P_SPACE.replace_all(&spaced, "");
-    let cleaned = cleaned.replace('\t', " ");
-    let cleaned = cleaned.replace(&['@', '#', '%', '&', '*', '(', ')'][..], "");
-    let cleaned = cleaned.replace("  ", " ");
+/// Unicode normalization and apostrophe/dash canonicalization, applied before cleaning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Normalizer {
+    pub form: Option<Normalization>,
+    pub apostrophe: Option<char>,
+    pub dash: Option<char>,
+}
+
+impl Normalizer {
+    pub fn is_active(&self) -> bool {
+        self.form.is_some() || self.apostrophe.is_some() || self.dash.is_some()
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        let text: String = match self.form {
+            Some(Normalization::Nfc) => text.nfc().collect(),
+            Some(Normalization::Nfkc) => text.nfkc().collect(),
+            None => text.to_string(),
+        };
+
+        text.chars()
+            .map(|c| match (self.apostrophe, self.dash) {
+                (Some(apostrophe), _) if APOSTROPHES.contains(&c) => apostrophe,
+                (_, Some(dash)) if DASHES.contains(&c) => dash,
+                _ => c,
+            })
+            .collect()
+    }
+}
+
+/// Latin letters paired with their Cyrillic look-alikes.
+const HOMOGLYPHS: [(char, char); 20] = [
+    ('a', 'а'),
+    ('c', 'с'),
+    ('e', 'е'),
+    ('i', 'і'),
+    ('o', 'о'),
+    ('p', 'р'),
+    ('x', 'х'),
+    ('y', 'у'),
+    ('A', 'А'),
+    ('B', 'В'),
+    ('C', 'С'),
+    ('E', 'Е'),
+    ('H', 'Н'),
+    ('I', 'І'),
+    ('K', 'К'),
+    ('M', 'М'),
+    ('O', 'О'),
+    ('P', 'Р'),
+    ('T', 'Т'),
+    ('X', 'Х'),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Cyrillic,
+}
+
+fn script(c: char) -> Option<Script> {
+    match c {
+        'A'..='Z' | 'a'..='z' | '\u{00C0}'..='\u{024F}' => Some(Script::Latin),
+        '\u{0400}'..='\u{052F}' => Some(Script::Cyrillic),
+        _ => None,
+    }
+}
 
-    cleaned.trim().to_string()
+/// The letter of the other script that looks like `c`, if any.
+fn look_alike(c: char) -> Option<char> {
+    HOMOGLYPHS.iter().find_map(|&(latin, cyrillic)| match c {
+        _ if c == latin => Some(cyrillic),
+        _ if c == cyrillic => Some(latin),
+        _ => None,
+    })
+}
+
+/// Map look-alike letters in words mixing Latin and Cyrillic to the dominant script of the
+/// word, returning the repaired text and the number of replaced characters. The dominant script
+/// is the one with more letters that have no look-alike (e.g. `п`, `в`, `s`), falling back to
+/// all letters; words without a dominant script are left as they are.
+pub fn fix_homoglyphs(text: &str) -> (String, usize) {
+    let mut fixed = String::with_capacity(text.len());
+    let mut word = Vec::new();
+    let mut repaired = 0;
+
+    for c in text.chars() {
+        if c.is_alphabetic() {
+            word.push(c);
+        } else {
+            repaired += fix_word(&mut word);
+            fixed.extend(word.drain(..));
+            fixed.push(c);
+        }
+    }
+    repaired += fix_word(&mut word);
+    fixed.extend(word);
+
+    (fixed, repaired)
+}
+
+fn fix_word(word: &mut [char]) -> usize {
+    let count = |wanted: Script, distinctive: bool| {
+        word.iter()
+            .filter(|&&c| script(c) == Some(wanted) && !(distinctive && look_alike(c).is_some()))
+            .count()
+    };
+    if count(Script::Latin, false) == 0 || count(Script::Cyrillic, false) == 0 {
+        return 0;
+    }
+
+    let by_distinctive = count(Script::Latin, true).cmp(&count(Script::Cyrillic, true));
+    let by_total = count(Script::Latin, false).cmp(&count(Script::Cyrillic, false));
+    let minority = match by_distinctive.then(by_total) {
+        Ordering::Greater => Script::Cyrillic,
+        Ordering::Less => Script::Latin,
+        Ordering::Equal => return 0,
+    };
+
+    let mut repaired = 0;
+    for c in word.iter_mut().filter(|c| script(**c) == Some(minority)) {
+        if let Some(replacement) = look_alike(*c) {
+            *c = replacement;
+            repaired += 1;
+        }
+    }
+    repaired
+}
+
+static HTML_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<[A-Za-z/!][^<>]*>").expect("HTML tag regex must compile"));
+static HTML_ENTITY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&(?:[A-Za-z][A-Za-z0-9]*|#[0-9]+|#[xX][0-9A-Fa-f]+);")
+        .expect("HTML entity regex must compile")
+});
+static URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(?:https?://|ftp://|www\.)[^\s<>"]*[^\s<>".,;:!?)\]']"#)
+        .expect("URL regex must compile")
+});
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[\w.%+-]+@[\w-]+(?:\.[\w-]+)*\.\w{2,}\b").expect("e-mail regex must compile")
+});
+static MENTION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\B@\w+").expect("mention regex must compile"));
+static HASHTAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\B#\w+").expect("hashtag regex must compile"));
+
+/// Remove every kind of markup in `kinds` from `text`, in [`Markup`] order so that e-mail
+/// addresses are gone before mentions are matched. Removed spans become spaces, after which runs
+/// of whitespace are collapsed; text without markup is returned unchanged.
+pub fn strip_markup(text: &str, kinds: &[Markup]) -> String {
+    let mut kinds = kinds.to_vec();
+    kinds.sort();
+    kinds.dedup();
+
+    let mut stripped = Cow::Borrowed(text);
+    for kind in kinds {
+        if let Cow::Owned(removed) = remove_markup(&stripped, kind) {
+            stripped = Cow::Owned(removed);
+        }
+    }
+
+    match stripped {
+        Cow::Borrowed(text) => text.to_string(),
+        Cow::Owned(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+    }
+}
+
+fn remove_markup(text: &str, kind: Markup) -> Cow<'_, str> {
+    match kind {
+        Markup::Html => match HTML_TAG.replace_all(text, " ") {
+            Cow::Borrowed(text) => HTML_ENTITY.replace_all(text, " "),
+            Cow::Owned(text) => Cow::Owned(HTML_ENTITY.replace_all(&text, " ").into_owned()),
+        },
+        Markup::Urls => URL.replace_all(text, " "),
+        Markup::Emails => EMAIL.replace_all(text, " "),
+        Markup::Mentions => MENTION.replace_all(text, " "),
+        Markup::Hashtags => HASHTAG.replace_all(text, " "),
+    }
+}
+
+/// Names of the built-in profiles accepted by `--clean-profile`.
+pub const BUILTIN_PROFILES: [&str; 4] = ["default", "asr", "web", "minimal"];
+
+/// One step of a cleaning profile. Custom profiles list steps in a TOML file as
+/// `[[steps]]` tables tagged with `step = "<name>"`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum CleanStep {
+    /// Replace every run of whitespace with a single space.
+    CollapseWhitespace,
+    /// Drop everything except letters, punctuation, and spaces, optionally keeping digits and
+    /// the characters listed in `keep`.
+    StripSymbols {
+        #[serde(default)]
+        keep_digits: bool,
+        #[serde(default)]
+        keep: String,
+    },
+    /// Drop every character listed in `chars`.
+    RemoveChars {
+        chars: String,
+    },
+    /// Drop `(...)`, `[...]`, and `{...}` spans together with their contents.
+    DropBracketed,
+    Lowercase,
+    /// Replace every occurrence of the literal `from` with `to`.
+    Replace {
+        from: String,
+        to: String,
+    },
+    /// Replace every match of the regular expression `pattern` with `with`.
+    RegexReplace {
+        pattern: String,
+        #[serde(default)]
+        with: String,
+    },
+    Trim,
+    /// Remove HTML tags and entities.
+    StripHtml,
+    /// Remove `http://`, `https://`, `ftp://`, and `www.` links.
+    StripUrls,
+    /// Remove e-mail addresses.
+    StripEmails,
+    /// Remove `@name` mentions.
+    StripMentions,
+    /// Remove `#tag` hashtags.
+    StripHashtags,
+}
+
+/// Custom profile file layout.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    steps: Vec<CleanStep>,
+}
+
+/// Ordered cleaning steps applied by `--clean`, from a built-in profile or a TOML file.
+#[derive(Clone, Debug)]
+pub struct CleanProfile {
+    name: String,
+    rules: Vec<Rule>,
+}
+
+/// A [`CleanStep`] with its regular expression compiled.
+#[derive(Clone, Debug)]
+enum Rule {
+    Regex { regex: Regex, replacement: String },
+    RemoveChars(Vec<char>),
+    Literal { from: String, to: String },
+    Lowercase,
+    Trim,
+    Markup(Markup),
+}
+
+impl CleanProfile {
+    /// Build a profile from `steps`, compiling their regular expressions.
+    pub fn new(name: &str, steps: &[CleanStep]) -> Result<Self> {
+        let rules = steps.iter().map(Rule::compile).collect::<Result<_>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            rules,
+        })
+    }
+
+    /// Resolve a built-in profile name, or load a TOML profile from any other value.
+    pub fn parse(raw: &str) -> Result<Self> {
+        match builtin_steps(raw) {
+            Some(steps) => Self::new(raw, &steps),
+            None => Self::from_file(Path::new(raw)),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| {
+            format!(
+                "Cannot read cleaning profile {:?}; built-in profiles are {}",
+                path,
+                BUILTIN_PROFILES.join(", ")
+            )
+        })?;
+        let file: ProfileFile =
+            toml::from_str(&raw).with_context(|| format!("Invalid cleaning profile {:?}", path))?;
+        Self::new(&path.display().to_string(), &file.steps)
+            .with_context(|| format!("Invalid cleaning profile {:?}", path))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Apply every step of the profile in order.
+    pub fn clean(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            text = match rule {
+                Rule::Regex { regex, replacement } => {
+                    regex.replace_all(&text, replacement.as_str()).into_owned()
+                }
+                Rule::RemoveChars(chars) => text.replace(chars.as_slice(), ""),
+                Rule::Literal { from, to } => text.replace(from.as_str(), to),
+                Rule::Lowercase => text.to_lowercase(),
+                Rule::Trim => text.trim().to_string(),
+                Rule::Markup(kind) => remove_markup(&text, *kind).into_owned(),
+            };
+        }
+        text
+    }
+}
+
+impl Default for CleanProfile {
+    fn default() -> Self {
+        Self::parse("default").expect("built-in profiles must compile")
+    }
+}
+
+impl Rule {
+    fn compile(step: &CleanStep) -> Result<Self> {
+        static WHITESPACE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"\s+").expect("whitespace regex must compile"));
+        static BRACKETED: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"\([^()]*\)|\[[^\[\]]*\]|\{[^{}]*\}").expect("bracket regex must compile")
+        });
+        let regex = |regex: Regex, replacement: &str| Rule::Regex {
+            regex,
+            replacement: replacement.to_string(),
+        };
+
+        let rule = match step {
+            CleanStep::CollapseWhitespace => regex(WHITESPACE.clone(), " "),
+            CleanStep::StripSymbols { keep_digits, keep } => {
+                let digits = if *keep_digits { r"\p{N}" } else { "" };
+                let keep: String = keep
+                    .chars()
+                    .map(|c| regex::escape(&c.to_string()))
+                    .collect();
+                let pattern = format!(r"[^ \p{{L}}\p{{P}}{digits}{keep}]");
+                regex(Regex::new(&pattern).expect("symbol regex must compile"), "")
+            }
+            CleanStep::RemoveChars { chars } => Rule::RemoveChars(chars.chars().collect()),
+            CleanStep::DropBracketed => regex(BRACKETED.clone(), ""),
+            CleanStep::Lowercase => Rule::Lowercase,
+            CleanStep::Replace { from, to } => {
+                if from.is_empty() {
+                    return Err(anyhow!("replace step needs a non-empty `from`"));
+                }
+                Rule::Literal {
+                    from: from.clone(),
+                    to: to.clone(),
+                }
+            }
+            CleanStep::RegexReplace { pattern, with } => regex(
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid regex_replace pattern '{pattern}'"))?,
+                with,
+            ),
+            CleanStep::Trim => Rule::Trim,
+            CleanStep::StripHtml => Rule::Markup(Markup::Html),
+            CleanStep::StripUrls => Rule::Markup(Markup::Urls),
+            CleanStep::StripEmails => Rule::Markup(Markup::Emails),
+            CleanStep::StripMentions => Rule::Markup(Markup::Mentions),
+            CleanStep::StripHashtags => Rule::Markup(Markup::Hashtags),
+        };
+
+        Ok(rule)
+    }
+}
+
+/// Steps of a built-in profile:
+/// - `default`: remove all symbols except letters, spaces, and punctuation for Ukrainian,
+///   Russian, and English texts.
+/// - `asr`: drop bracketed annotations such as `[noise]`, strip symbols and digits, lowercase.
+/// - `web`: remove HTML, URLs, and e-mail addresses, then strip symbols and markup leftovers
+///   but keep digits.
+/// - `minimal`: only normalise whitespace.
+fn builtin_steps(name: &str) -> Option<Vec<CleanStep>> {
+    use CleanStep::*;
+
+    let strip_symbols = |keep_digits| StripSymbols {
+        keep_digits,
+        keep: String::new(),
+    };
+    let steps = match name {
+        "default" => vec![
+            CollapseWhitespace,
+            strip_symbols(false),
+            RemoveChars {
+                chars: "@#%&*()".to_string(),
+            },
+            Replace {
+                from: "  ".to_string(),
+                to: " ".to_string(),
+            },
+            Trim,
+        ],
+        "asr" => vec![
+            DropBracketed,
+            CollapseWhitespace,
+            strip_symbols(false),
+            RemoveChars {
+                chars: "@#%&*".to_string(),
+            },
+            CollapseWhitespace,
+            Lowercase,
+            Trim,
+        ],
+        "web" => vec![
+            StripHtml,
+            StripUrls,
+            StripEmails,
+            CollapseWhitespace,
+            strip_symbols(true),
+            RemoveChars {
+                chars: "@#%&*()[]{}/\\_".to_string(),
+            },
+            CollapseWhitespace,
+            Trim,
+        ],
+        "minimal" => vec![CollapseWhitespace, Trim],
+        _ => return None,
+    };
+
+    Some(steps)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn clean_text(text: &str) -> String {
+        CleanProfile::default().clean(text)
+    }
+
     #[test]
     fn clean_text_removes_symbols_and_digits() {
         let raw = "Hello, world! 123 \n\t Привіт, світ! @#$%^&*() 456";
@@ -54,4 +477,118 @@ mod tests {
         let cleaned = clean_text(raw);
         assert_eq!(cleaned, "Hello!!! How's it going???");
     }
+
+    #[test]
+    fn normalizer_composes_and_canonicalizes_apostrophes_and_dashes() {
+        let normalizer = Normalizer {
+            form: Some(Normalization::Nfc),
+            apostrophe: Some('ʼ'),
+            dash: Some('-'),
+        };
+        let decomposed = "мі\u{0438}\u{0306} п`ять — сім'я – \u{0456}\u{0308}жак";
+
+        assert_eq!(normalizer.normalize(decomposed), "мій пʼять - сімʼя - їжак");
+        assert!(!Normalizer::default().is_active());
+    }
+
+    #[test]
+    fn nfkc_folds_compatibility_characters() {
+        let normalizer = Normalizer {
+            form: Some(Normalization::Nfkc),
+            ..Normalizer::default()
+        };
+
+        assert_eq!(normalizer.normalize("ﬁle Ｋyiv №１"), "file Kyiv No1");
+    }
+
+    #[test]
+    fn fix_homoglyphs_maps_letters_to_the_dominant_script() {
+        assert_eq!(
+            fix_homoglyphs("пpивіт, cвіт! Hеllo"),
+            ("привіт, світ! Hello".to_string(), 3)
+        );
+        assert_eq!(fix_homoglyphs("сoр"), ("сор".to_string(), 1));
+        assert_eq!(
+            fix_homoglyphs("iPhone та Київ"),
+            ("iPhone та Київ".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn strip_markup_removes_selected_kinds_only() {
+        let raw = "<p>Пишіть&nbsp;на info@example.com або www.example.com/uk.</p> @admin #новини";
+        let strip = |kinds: &[Markup]| strip_markup(raw, kinds);
+
+        assert_eq!(
+            strip(&[
+                Markup::Hashtags,
+                Markup::Mentions,
+                Markup::Emails,
+                Markup::Urls,
+                Markup::Html
+            ]),
+            "Пишіть на або ."
+        );
+        assert_eq!(
+            strip(&[Markup::Mentions]),
+            "<p>Пишіть&nbsp;на info@example.com або www.example.com/uk.</p> #новини"
+        );
+        assert_eq!(strip(&[]), raw);
+        assert_eq!(
+            strip_markup("Слава Україні!", &[Markup::Urls]),
+            "Слава Україні!"
+        );
+    }
+
+    #[test]
+    fn builtin_profiles_apply_their_steps() {
+        let raw = "[noise] Привіт,  Світе! 42 (сміх) @user";
+        let clean = |name| CleanProfile::parse(name).unwrap().clean(raw);
+
+        assert_eq!(clean("asr"), "привіт, світе! user");
+        assert_eq!(clean("web"), "noise Привіт, Світе! 42 сміх user");
+        assert_eq!(clean("minimal"), "[noise] Привіт, Світе! 42 (сміх) @user");
+    }
+
+    #[test]
+    fn custom_profiles_load_ordered_steps_from_toml() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("profile.toml");
+        fs::write(
+            &path,
+            concat!(
+                "[[steps]]\n",
+                "step = \"strip_symbols\"\n",
+                "keep_digits = true\n",
+                "keep = \"+\"\n",
+                "\n",
+                "[[steps]]\n",
+                "step = \"regex_replace\"\n",
+                "pattern = \"\\\\d+\"\n",
+                "with = \"N\"\n",
+            ),
+        )
+        .unwrap();
+
+        let profile = CleanProfile::parse(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(profile.clean("Дзвоніть: +380 44 123!"), "Дзвоніть: +N N N!");
+    }
+
+    #[test]
+    fn custom_profiles_reject_unknown_steps_and_bad_patterns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("profile.toml");
+
+        fs::write(&path, "[[steps]]\nstep = \"shout\"\n").unwrap();
+        assert!(CleanProfile::from_file(&path).is_err());
+
+        fs::write(
+            &path,
+            "[[steps]]\nstep = \"regex_replace\"\npattern = \"(\"\n",
+        )
+        .unwrap();
+        let err = CleanProfile::from_file(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid regex_replace pattern"));
+    }
 }
@@ -91,6 +91,18 @@ fn read_parquet(path: &Path) -> PolarsResult<DataFrame> {
     reader.finish()
 }
 
+fn reject_reasons(df: &DataFrame) -> Vec<Option<String>> {
+    df.column("reject_reason")
+        .unwrap()
+        .cast(&DataType::String)
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .map(|reason| reason.map(str::to_string))
+        .collect()
+}
+
 #[test]
 fn writes_invalid_rows_for_single_file() {
     let tmp = tempdir().unwrap();
@@ -370,6 +382,50 @@ fn clean_flag_removes_non_letter_characters() {
     assert!(!texts.iter().any(|t| t == &Some("Привіт, Україно! 😊 123")));
 }
 
+#[test]
+fn clean_column_keeps_original_text() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--clean")
+        .arg("--clean-column")
+        .arg("transcription_clean");
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    let texts: Vec<_> = df
+        .column("transcription")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(
+        texts,
+        vec![Some("Привіт світ!"), Some("Привіт, Україно! 😊 123")]
+    );
+    let cleaned: Vec<_> = df
+        .column("transcription_clean")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(
+        cleaned,
+        vec![Some("Привіт світ!"), Some("Привіт, Україно!")]
+    );
+}
+
 #[test]
 fn uses_custom_column_when_provided() {
     let tmp = tempdir().unwrap();
@@ -408,6 +464,36 @@ fn uses_custom_column_when_provided() {
     assert!(texts.iter().any(|t| t == &Some("Привіт світ!")));
 }
 
+#[test]
+fn existing_paths_with_glob_characters_are_read_literally() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("clip[1].parquet");
+    let out_path = tmp.path().join("out.parquet");
+    let output_dir = tmp.path().join("filtered");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i").arg(&in_path).arg("-o").arg(&out_path);
+
+    cmd.assert().success();
+    assert_eq!(read_parquet(&out_path).unwrap().height(), 2);
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(tmp.path().join("clip*.parquet"))
+        .arg("-o")
+        .arg(&output_dir);
+
+    cmd.assert().success();
+    assert_eq!(
+        read_parquet(&output_dir.join("clip[1].parquet"))
+            .unwrap()
+            .height(),
+        2
+    );
+}
+
 #[test]
 fn input_dir_without_parquet_fails() {
     let tmp = tempdir().unwrap();
@@ -423,7 +509,8 @@ fn input_dir_without_parquet_fails() {
 
     cmd.assert()
         .failure()
-        .stderr(contains("No Parquet files found in input directory"));
+        .stderr(contains("No parquet input files found in input directory"))
+        .stderr(contains("pass --input-format"));
 }
 
 #[test]
@@ -469,3 +556,1483 @@ fn processes_parquet_files_with_struct_columns() {
     let audio = df.column("audio").unwrap();
     assert!(matches!(audio.dtype(), DataType::Struct(_)));
 }
+
+#[test]
+fn annotate_keeps_all_rows_and_appends_detection_columns() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("annotated.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--annotate");
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 5);
+
+    let langs: Vec<_> = df
+        .column("detected_lang")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(langs[0], Some("uk"));
+    assert_eq!(langs[1], Some("en"));
+    assert_eq!(langs[3], None);
+    assert_eq!(langs[4], None);
+
+    let confidences: Vec<_> = df
+        .column("lang_confidence")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(confidences[0].is_some_and(|c| c > 0.0 && c <= 1.0));
+    assert_eq!(confidences[3], None);
+}
+
+#[test]
+fn annotate_with_output_invalid_still_filters_rows() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("valid.parquet");
+    let invalid_path = tmp.path().join("invalid.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--clean")
+        .arg("--annotate");
+
+    cmd.assert().success();
+
+    let valid = read_parquet(&out_path).unwrap();
+    let invalid = read_parquet(&invalid_path).unwrap();
+    assert_eq!(valid.height(), 2);
+    assert_eq!(invalid.height(), 3);
+
+    let valid_langs: Vec<_> = valid
+        .column("detected_lang")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert!(valid_langs.iter().all(|lang| lang == &Some("uk")));
+    assert!(invalid.column("lang_confidence").is_ok());
+}
+
+#[test]
+fn top_k_appends_ranked_candidate_structs() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--top-k")
+        .arg("3");
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 2);
+
+    let candidates = df.column("lang_candidates").unwrap().list().unwrap();
+    assert!(matches!(
+        candidates.inner_dtype(),
+        DataType::Struct(fields) if fields.len() == 2
+    ));
+
+    let first = candidates.get_as_series(0).unwrap();
+    assert!(!first.is_empty() && first.len() <= 3);
+    let first = first.struct_().unwrap();
+    let langs = first.field_by_name("lang").unwrap();
+    assert_eq!(langs.str().unwrap().get(0), Some("uk"));
+    let confidences: Vec<f64> = first
+        .field_by_name("confidence")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    assert!(confidences.windows(2).all(|pair| pair[0] >= pair[1]));
+}
+
+#[test]
+fn candidates_restrict_detector_languages() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--candidates")
+        .arg("ru,be,en")
+        .arg("--annotate")
+        .env("RUST_LOG", "info");
+
+    cmd.assert()
+        .success()
+        .stderr(contains("candidates = {be, en, ru, uk}"));
+
+    let df = read_parquet(&out_path).unwrap();
+    let langs: Vec<_> = df
+        .column("detected_lang")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect();
+    assert!(
+        langs
+            .iter()
+            .all(|lang| ["uk", "ru", "be", "en"].contains(lang))
+    );
+}
+
+#[test]
+fn per_language_thresholds_override_global_threshold() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("-l")
+        .arg("uk:0.0")
+        .arg("-l")
+        .arg("en:1.0")
+        .arg("--threshold")
+        .arg("0.0");
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 2);
+    let col = df.column("transcription").unwrap().str().unwrap();
+    let texts: Vec<_> = col.into_iter().collect();
+    assert!(!texts.iter().any(|t| t == &Some("Hello, world!")));
+}
+
+#[test]
+fn min_margin_rejects_ambiguous_rows() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("valid.parquet");
+    let invalid_path = tmp.path().join("invalid.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    // A margin of 1.0 can never be exceeded, so every Ukrainian row becomes ambiguous.
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--threshold")
+        .arg("0.0")
+        .arg("--min-margin")
+        .arg("1.0");
+
+    cmd.assert().success();
+
+    let valid = read_parquet(&out_path).unwrap();
+    let invalid = read_parquet(&invalid_path).unwrap();
+    assert_eq!(valid.height(), 0);
+    assert_eq!(invalid.height(), 5);
+
+    let reasons = reject_reasons(&invalid);
+    assert_eq!(
+        reasons
+            .iter()
+            .filter(|r| r.as_deref() == Some("ambiguous"))
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn exclude_lang_drops_only_excluded_languages() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_custom_parquet(
+        &in_path,
+        "transcription",
+        &[
+            Some("Привіт світ!"),
+            Some("Hello, world! How are you doing today?"),
+            Some("Привет, как у тебя дела сегодня?"),
+            Some("123 😊"),
+        ],
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--exclude-lang")
+        .arg("ru")
+        .arg("--clean")
+        .arg("--keep-empty")
+        .env("RUST_LOG", "info");
+
+    cmd.assert()
+        .success()
+        .stderr(contains("langs = all except"));
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 3);
+    let col = df.column("transcription").unwrap().str().unwrap();
+    let texts: Vec<_> = col.into_iter().collect();
+    assert!(
+        !texts
+            .iter()
+            .any(|t| t == &Some("Привет, как у тебя дела сегодня?"))
+    );
+}
+
+#[test]
+fn exclude_lang_conflicts_with_lang() {
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg("in.parquet")
+        .arg("-o")
+        .arg("out.parquet")
+        .arg("--lang")
+        .arg("uk")
+        .arg("--exclude-lang")
+        .arg("ru");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn languages_subcommand_lists_supported_languages() {
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("languages");
+
+    cmd.assert()
+        .success()
+        .stdout(contains("uk\tukr\tUkrainian\tукраїнська"))
+        .stdout(contains("it\tita\tItalian"))
+        .stdout(contains("be\tbel\tBelarusian"));
+}
+
+#[test]
+fn invalid_output_records_reject_reasons() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("valid.parquet");
+    let invalid_path = tmp.path().join("invalid.parquet");
+
+    write_custom_parquet(
+        &in_path,
+        "transcription",
+        &[
+            Some("Привіт світ!"),
+            Some("Hello, world!"),
+            None,
+            Some(""),
+            Some("😊 123"),
+            Some("Привіт, як справи у тебе сьогодні?"),
+        ],
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .arg("-l")
+        .arg("uk:1.0")
+        .arg("--clean");
+
+    cmd.assert().success();
+
+    let invalid = read_parquet(&invalid_path).unwrap();
+    assert_eq!(invalid.height(), 6);
+    assert!(matches!(
+        invalid.column("reject_reason").unwrap().dtype(),
+        DataType::Enum(_, _) | DataType::Categorical(_, _)
+    ));
+
+    let ids: Vec<_> = invalid
+        .column("id")
+        .unwrap()
+        .i32()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    let reasons = reject_reasons(&invalid);
+    let reason_for = |id: i32| {
+        let row = ids.iter().position(|candidate| *candidate == id).unwrap();
+        reasons[row].as_deref()
+    };
+    assert_eq!(reason_for(1), Some("wrong_language"));
+    assert_eq!(reason_for(2), Some("null"));
+    assert_eq!(reason_for(3), Some("empty"));
+    assert_eq!(reason_for(4), Some("empty_after_clean"));
+    assert_eq!(reason_for(5), Some("below_threshold"));
+}
+
+#[test]
+fn small_batches_produce_the_same_outputs() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("valid.parquet");
+    let invalid_path = tmp.path().join("invalid.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .arg("-l")
+        .arg("uk")
+        .arg("--clean")
+        .arg("--annotate")
+        .arg("--batch-rows")
+        .arg("2")
+        .env("RUST_LOG", "info");
+
+    cmd.assert()
+        .success()
+        .stderr(contains("Filtered 5 rows -> 2 rows kept, 3 rows rejected"));
+
+    let valid = read_parquet(&out_path).unwrap();
+    let invalid = read_parquet(&invalid_path).unwrap();
+    assert_eq!(valid.height(), 2);
+    assert_eq!(invalid.height(), 3);
+
+    let ids: Vec<_> = valid
+        .column("id")
+        .unwrap()
+        .i32()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    assert_eq!(ids, vec![0, 2]);
+    assert_eq!(reject_reasons(&invalid).len(), 3);
+}
+
+#[test]
+fn batches_spanning_several_row_groups_are_written_as_one_chunk() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+    let arrow_path = tmp.path().join("out.arrow");
+
+    let mut df = df![
+        "id" => [0i32, 1, 2, 3, 4, 5],
+        "transcription" => [
+            "Привіт світ!",
+            "Hello, world!",
+            "Good morning, everyone!",
+            "Добрий день, друзі!",
+            "See you tomorrow.",
+            "Thank you very much.",
+        ],
+    ]
+    .unwrap();
+    ParquetWriter::new(File::create(&in_path).unwrap())
+        .with_row_group_size(Some(1))
+        .finish(&mut df)
+        .unwrap();
+
+    // Every batch covers six one-row chunks but keeps only two rows.
+    for out in [&out_path, &arrow_path] {
+        let mut cmd = Command::cargo_bin("babylonify").unwrap();
+        cmd.arg("-i")
+            .arg(&in_path)
+            .arg("-o")
+            .arg(out)
+            .arg("--lang")
+            .arg("uk")
+            .arg("--top-k")
+            .arg("2");
+
+        cmd.assert().success();
+    }
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 2);
+    let mut reader = ParquetReader::new(File::open(&out_path).unwrap());
+    assert_eq!(reader.get_metadata().unwrap().row_groups.len(), 1);
+    let arrow = IpcReader::new(File::open(&arrow_path).unwrap())
+        .finish()
+        .unwrap();
+    assert_eq!(arrow.height(), 2);
+}
+
+#[test]
+fn empty_input_produces_empty_output_with_schema() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("empty.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_custom_parquet(&in_path, "transcription", &[]).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i").arg(&in_path).arg("-o").arg(&out_path);
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 0);
+    assert!(df.column("transcription").is_ok());
+}
+
+#[test]
+fn recursive_mirrors_nested_directories() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("dataset");
+    let output_dir = tmp.path().join("filtered");
+    let invalid_dir = tmp.path().join("rejected");
+
+    let train = input_dir.join("lang=uk").join("split=train");
+    let test = input_dir.join("lang=uk").join("split=test");
+    fs::create_dir_all(&train).unwrap();
+    fs::create_dir_all(&test).unwrap();
+    write_input_parquet(&train.join("part-0001.parquet")).unwrap();
+    write_input_parquet(&test.join("part-0001.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--output-invalid")
+        .arg(&invalid_dir)
+        .arg("--recursive")
+        .arg("-l")
+        .arg("uk");
+
+    cmd.assert().success();
+
+    for split in ["split=train", "split=test"] {
+        let relative = Path::new("lang=uk").join(split).join("part-0001.parquet");
+        let valid = read_parquet(&output_dir.join(&relative)).unwrap();
+        let invalid = read_parquet(&invalid_dir.join(&relative)).unwrap();
+        assert_eq!(valid.height(), 2);
+        assert_eq!(invalid.height(), 3);
+    }
+}
+
+#[test]
+fn non_recursive_ignores_nested_directories() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("dataset");
+    let output_dir = tmp.path().join("filtered");
+
+    let nested = input_dir.join("nested");
+    fs::create_dir_all(&nested).unwrap();
+    write_input_parquet(&input_dir.join("top.parquet")).unwrap();
+    write_input_parquet(&nested.join("inner.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i").arg(&input_dir).arg("-o").arg(&output_dir);
+
+    cmd.assert().success();
+
+    assert!(output_dir.join("top.parquet").exists());
+    assert!(!output_dir.join("nested").exists());
+}
+
+#[test]
+fn processes_repeated_inputs_and_glob_patterns() {
+    let tmp = tempdir().unwrap();
+    let shards = tmp.path().join("shards");
+    let extra = tmp.path().join("extra");
+    let output_dir = tmp.path().join("filtered");
+    fs::create_dir_all(&shards).unwrap();
+    fs::create_dir_all(&extra).unwrap();
+
+    write_input_parquet(&shards.join("train-0.parquet")).unwrap();
+    write_input_parquet(&shards.join("train-1.parquet")).unwrap();
+    write_input_parquet(&shards.join("test-0.parquet")).unwrap();
+    write_input_parquet(&extra.join("bonus.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(shards.join("train-*.parquet"))
+        .arg("-i")
+        .arg(extra.join("bonus.parquet"))
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("-l")
+        .arg("uk");
+
+    cmd.assert().success();
+
+    for name in ["train-0.parquet", "train-1.parquet", "bonus.parquet"] {
+        let df = read_parquet(&output_dir.join(name)).unwrap();
+        assert_eq!(df.height(), 2);
+    }
+    assert!(!output_dir.join("test-0.parquet").exists());
+}
+
+#[test]
+fn fails_when_inputs_map_to_the_same_output() {
+    let tmp = tempdir().unwrap();
+    let first = tmp.path().join("a");
+    let second = tmp.path().join("b");
+    let output_dir = tmp.path().join("filtered");
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+
+    write_input_parquet(&first.join("part.parquet")).unwrap();
+    write_input_parquet(&second.join("part.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(tmp.path().join("*").join("part.parquet"))
+        .arg("-o")
+        .arg(&output_dir);
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("would both be written to"));
+    assert!(!output_dir.join("part.parquet").exists());
+}
+
+#[test]
+fn fails_when_glob_matches_nothing() {
+    let tmp = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(tmp.path().join("missing-*.parquet"))
+        .arg("-o")
+        .arg(tmp.path().join("filtered"));
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("No files match input pattern"));
+}
+
+#[test]
+fn processes_directory_files_concurrently() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("filtered");
+    let invalid_dir = tmp.path().join("rejected");
+    fs::create_dir_all(&input_dir).unwrap();
+
+    let names: Vec<String> = (0..5).map(|i| format!("shard-{i}.parquet")).collect();
+    for name in &names {
+        write_input_parquet(&input_dir.join(name)).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--output-invalid")
+        .arg(&invalid_dir)
+        .arg("--file-jobs")
+        .arg("3")
+        .arg("-l")
+        .arg("uk")
+        .env("RUST_LOG", "info");
+
+    let assert = cmd.assert().success();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert_eq!(stderr.matches("Filtered 5 rows").count(), names.len());
+
+    for name in &names {
+        assert_eq!(read_parquet(&output_dir.join(name)).unwrap().height(), 2);
+        assert_eq!(read_parquet(&invalid_dir.join(name)).unwrap().height(), 3);
+    }
+}
+
+#[test]
+fn resume_skips_completed_outputs_and_redoes_broken_ones() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("filtered");
+    fs::create_dir_all(&input_dir).unwrap();
+    write_input_parquet(&input_dir.join("done.parquet")).unwrap();
+    write_input_parquet(&input_dir.join("broken.parquet")).unwrap();
+
+    let mut first_run = Command::cargo_bin("babylonify").unwrap();
+    first_run
+        .arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir);
+    first_run.assert().success();
+
+    // Simulate a run that died while writing one of the outputs.
+    fs::write(output_dir.join("broken.parquet"), b"PAR1 truncated").unwrap();
+
+    let mut resumed = Command::cargo_bin("babylonify").unwrap();
+    resumed
+        .arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--resume")
+        .env("RUST_LOG", "info");
+
+    resumed
+        .assert()
+        .success()
+        .stderr(contains("skipping"))
+        .stderr(contains("done.parquet"))
+        .stderr(contains("1 of 2 file(s) already complete"));
+
+    assert_eq!(
+        read_parquet(&output_dir.join("broken.parquet"))
+            .unwrap()
+            .height(),
+        2
+    );
+}
+
+#[test]
+fn failed_runs_leave_no_partial_outputs() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("valid.parquet");
+    let blocker = tmp.path().join("blocker");
+    let invalid_path = blocker.join("invalid.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+    fs::write(&blocker, "not a directory").unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("Failed to create output directory"));
+
+    assert!(!out_path.exists());
+    let leftovers: Vec<_> = fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "temporary files left: {leftovers:?}");
+}
+
+#[test]
+fn report_records_per_file_and_total_statistics() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("filtered");
+    let report_path = tmp.path().join("report.json");
+
+    fs::create_dir_all(&input_dir).unwrap();
+    write_input_parquet(&input_dir.join("first.parquet")).unwrap();
+    write_input_parquet(&input_dir.join("second.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--report")
+        .arg(&report_path);
+
+    cmd.assert().success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+
+    assert_eq!(report["settings"]["langs"]["uk"], 0.6);
+    assert_eq!(report["skipped_files"], 0);
+    assert!(report["elapsed_secs"].is_f64());
+
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(
+        files[0]["input"]
+            .as_str()
+            .unwrap()
+            .ends_with("first.parquet")
+    );
+    assert_eq!(files[0]["total_rows"], 5);
+    assert_eq!(files[0]["kept_rows"], 2);
+    assert_eq!(files[0]["rejected_rows"], 3);
+
+    let totals = &report["totals"];
+    assert_eq!(totals["total_rows"], 10);
+    assert_eq!(totals["kept_rows"], 4);
+    assert_eq!(totals["reject_reasons"]["null"], 2);
+    assert_eq!(totals["reject_reasons"]["empty"], 2);
+    assert_eq!(totals["reject_reasons"]["wrong_language"], 2);
+    assert_eq!(totals["detected_languages"]["uk"], 4);
+    assert_eq!(totals["detected_languages"]["en"], 2);
+    let histogram: u64 = totals["confidence_histogram"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rows| rows.as_u64().unwrap())
+        .sum();
+    assert_eq!(histogram, 6);
+}
+
+#[test]
+fn split_by_language_writes_one_output_per_language() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let split_dir = tmp.path().join("by_lang");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("--split-by-language")
+        .arg(&split_dir)
+        .arg("--candidates")
+        .arg("uk,en");
+
+    cmd.assert().success();
+
+    let uk = read_parquet(&split_dir.join("uk").join("in.parquet")).unwrap();
+    let uk_ids: Vec<_> = uk
+        .column("id")
+        .unwrap()
+        .i32()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(uk_ids, vec![Some(0), Some(2)]);
+
+    let en = read_parquet(&split_dir.join("en").join("in.parquet")).unwrap();
+    let en_texts: Vec<_> = en
+        .column("transcription")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(en_texts, vec![Some("Hello, world!")]);
+
+    let und = read_parquet(&split_dir.join("und").join("in.parquet")).unwrap();
+    assert_eq!(
+        reject_reasons(&und),
+        vec![Some("null".to_string()), Some("empty".to_string())]
+    );
+
+    let partitions = fs::read_dir(&split_dir).unwrap().count();
+    assert_eq!(partitions, 3);
+}
+
+#[test]
+fn split_by_language_uses_only_the_given_candidates() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let split_dir = tmp.path().join("by_lang");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("--split-by-language")
+        .arg(&split_dir)
+        .arg("--candidates")
+        .arg("en,ru");
+
+    cmd.assert().success();
+    assert!(split_dir.join("en").join("in.parquet").exists());
+    assert!(!split_dir.join("uk").exists());
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("--split-by-language")
+        .arg(tmp.path().join("single"))
+        .arg("--candidates")
+        .arg("en");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("At least two candidate languages"));
+}
+
+#[test]
+fn write_options_control_compression_and_row_groups() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--annotate")
+        .arg("--batch-rows")
+        .arg("3")
+        .arg("--compression")
+        .arg("snappy")
+        .arg("--row-group-size")
+        .arg("2")
+        .arg("--statistics");
+
+    cmd.assert().success();
+
+    let mut reader = ParquetReader::new(File::open(&out_path).unwrap());
+    let metadata = reader.get_metadata().unwrap();
+    let row_groups: Vec<usize> = metadata
+        .row_groups
+        .iter()
+        .map(|row_group| row_group.num_rows())
+        .collect();
+    assert_eq!(row_groups, vec![2, 2, 1]);
+    let compression = metadata.row_groups[0].parquet_columns()[0].compression();
+    assert_eq!(format!("{compression:?}"), "Snappy");
+
+    let df = read_parquet(&out_path).unwrap();
+    assert_eq!(df.height(), 5);
+}
+
+#[test]
+fn rejects_compression_level_for_codec_without_levels() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_input_parquet(&in_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--compression")
+        .arg("lz4")
+        .arg("--compression-level")
+        .arg("3");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("not supported for lz4 compression"));
+    assert!(!out_path.exists());
+}
+
+#[test]
+fn max_rows_per_file_shards_each_input() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let other_path = tmp.path().join("other.parquet");
+    let output_dir = tmp.path().join("sharded");
+    let multi_output_dir = tmp.path().join("sharded_multi");
+
+    write_input_parquet(&in_path).unwrap();
+    write_input_parquet(&other_path).unwrap();
+
+    let part_heights = |dir: &Path| -> Vec<usize> {
+        let mut parts: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        parts.sort();
+        parts
+            .iter()
+            .map(|part| read_parquet(part).unwrap().height())
+            .collect()
+    };
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--annotate")
+        .arg("--max-rows-per-file")
+        .arg("2");
+
+    cmd.assert().success();
+    assert!(output_dir.join("part-00000.parquet").exists());
+    assert_eq!(part_heights(&output_dir), vec![2, 2, 1]);
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-i")
+        .arg(&other_path)
+        .arg("-o")
+        .arg(&multi_output_dir)
+        .arg("--annotate")
+        .arg("--max-rows-per-file")
+        .arg("2");
+
+    cmd.assert().success();
+    assert_eq!(part_heights(&multi_output_dir.join("in")), vec![2, 2, 1]);
+    assert_eq!(part_heights(&multi_output_dir.join("other")), vec![2, 2, 1]);
+}
+
+#[test]
+fn coalesce_combines_inputs_into_shared_parts() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("coalesced");
+
+    fs::create_dir_all(&input_dir).unwrap();
+    write_input_parquet(&input_dir.join("first.parquet")).unwrap();
+    write_input_parquet(&input_dir.join("second.parquet")).unwrap();
+    write_input_parquet(&input_dir.join("third.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--coalesce")
+        .arg("--max-rows-per-file")
+        .arg("4");
+
+    cmd.assert().success();
+
+    let mut parts: Vec<_> = fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    parts.sort();
+    assert_eq!(parts, vec!["part-00000.parquet", "part-00001.parquet"]);
+
+    let first = read_parquet(&output_dir.join("part-00000.parquet")).unwrap();
+    let second = read_parquet(&output_dir.join("part-00001.parquet")).unwrap();
+    assert_eq!(first.height(), 4);
+    assert_eq!(second.height(), 2);
+}
+
+#[test]
+fn coalesce_accepts_same_named_inputs() {
+    let tmp = tempdir().unwrap();
+    let first_dir = tmp.path().join("lang=uk");
+    let second_dir = tmp.path().join("lang=en");
+    let output_dir = tmp.path().join("coalesced");
+
+    fs::create_dir_all(&first_dir).unwrap();
+    fs::create_dir_all(&second_dir).unwrap();
+    write_input_parquet(&first_dir.join("part-0001.parquet")).unwrap();
+    write_input_parquet(&second_dir.join("part-0001.parquet")).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(first_dir.join("part-0001.parquet"))
+        .arg("-i")
+        .arg(second_dir.join("part-0001.parquet"))
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--coalesce");
+
+    cmd.assert().success();
+    assert_eq!(
+        read_parquet(&output_dir.join("part-00000.parquet"))
+            .unwrap()
+            .height(),
+        4
+    );
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(first_dir.join("part-0001.parquet"))
+        .arg("-i")
+        .arg(second_dir.join("part-0001.parquet"))
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--coalesce")
+        .arg("--file-jobs")
+        .arg("2");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("cannot be combined with --file-jobs"));
+}
+
+#[test]
+fn csv_input_writes_tsv_outputs() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.csv");
+    let out_path = tmp.path().join("out.tsv");
+    let invalid_path = tmp.path().join("invalid.tsv");
+
+    fs::write(
+        &in_path,
+        "id,transcription\n0,Привіт світ!\n1,\"Hello, world!\"\n2,\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path);
+
+    cmd.assert().success();
+
+    let kept = fs::read_to_string(&out_path).unwrap();
+    assert_eq!(kept, "id\ttranscription\n0\tПривіт світ!\n");
+
+    let invalid = CsvReadOptions::default()
+        .with_has_header(true)
+        .map_parse_options(|options| options.with_separator(b'\t'))
+        .try_into_reader_with_file_path(Some(invalid_path))
+        .unwrap()
+        .finish()
+        .unwrap();
+    assert_eq!(
+        reject_reasons(&invalid),
+        vec![Some("wrong_language".to_string()), Some("null".to_string())]
+    );
+}
+
+#[test]
+fn output_format_converts_directory_outputs() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("filtered");
+
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(
+        input_dir.join("first.jsonl"),
+        concat!(
+            "{\"id\": 0, \"transcription\": \"Привіт світ!\"}\n",
+            "{\"id\": 1, \"transcription\": \"Hello, world!\"}\n",
+        ),
+    )
+    .unwrap();
+    fs::write(
+        input_dir.join("second.json"),
+        "{\"id\": 2, \"transcription\": \"Добрий день, друзі!\"}\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--input-format")
+        .arg("ndjson")
+        .arg("--output-format")
+        .arg("ipc");
+
+    cmd.assert().success();
+
+    let read_ipc = |name: &str| {
+        IpcReader::new(File::open(output_dir.join(name)).unwrap())
+            .finish()
+            .unwrap()
+    };
+    let first = read_ipc("first.arrow");
+    assert_eq!(first.height(), 1);
+    assert_eq!(first.column("id").unwrap().dtype(), &DataType::Int64);
+    assert_eq!(read_ipc("second.arrow").height(), 1);
+    assert!(!output_dir.join("first.jsonl").exists());
+}
+
+#[test]
+fn input_dir_ignores_files_of_other_formats() {
+    let tmp = tempdir().unwrap();
+    let input_dir = tmp.path().join("inputs");
+    let output_dir = tmp.path().join("filtered");
+
+    fs::create_dir_all(&input_dir).unwrap();
+    write_input_parquet(&input_dir.join("data.parquet")).unwrap();
+    fs::write(
+        input_dir.join("manifest.csv"),
+        "file,rows\ndata.parquet,3\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i").arg(&input_dir).arg("-o").arg(&output_dir);
+
+    cmd.assert().success();
+    assert_eq!(
+        read_parquet(&output_dir.join("data.parquet"))
+            .unwrap()
+            .height(),
+        2
+    );
+    assert!(!output_dir.join("manifest.csv").exists());
+}
+
+#[test]
+fn top_k_rejects_csv_outputs_before_processing() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.csv");
+    let out_path = tmp.path().join("out.parquet");
+    let invalid_path = tmp.path().join("rejected.csv");
+    let split_dir = tmp.path().join("by_lang");
+
+    fs::write(
+        &in_path,
+        "id,transcription\n0,Привіт світ!\n1,\"Hello, world!\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .arg("--top-k")
+        .arg("2");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("--top-k adds a list column"))
+        .stderr(contains("rejected.csv"));
+    assert!(!out_path.exists());
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("--split-by-language")
+        .arg(&split_dir)
+        .arg("--top-k")
+        .arg("2");
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("--top-k adds a list column"));
+    assert!(!split_dir.join("uk").exists());
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("--split-by-language")
+        .arg(&split_dir)
+        .arg("--output-format")
+        .arg("parquet")
+        .arg("--top-k")
+        .arg("2");
+
+    cmd.assert().success();
+    let df = read_parquet(&split_dir.join("uk").join("in.parquet")).unwrap();
+    assert!(df.column("lang_candidates").is_ok());
+}
+
+#[test]
+fn input_format_is_required_for_unknown_extensions() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.txt");
+    let out_path = tmp.path().join("out.jsonl");
+
+    fs::write(&in_path, "transcription\nПривіт світ!\nHello, world!\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i").arg(&in_path).arg("-o").arg(&out_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(contains("pass --input-format"));
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--input-format")
+        .arg("tsv");
+
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(&out_path).unwrap(),
+        "{\"transcription\":\"Привіт світ!\"}\n"
+    );
+}
+
+#[test]
+fn stdin_filters_lines_to_stdout() {
+    let tmp = tempdir().unwrap();
+    let invalid_path = tmp.path().join("rejected.txt");
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--lang")
+        .arg("uk")
+        .arg("--clean")
+        .arg("--output-invalid")
+        .arg(&invalid_path)
+        .write_stdin("Привіт, Україно! 😊 123\nHello, world!\n\nДобрий день, друзі!\n");
+
+    cmd.assert()
+        .success()
+        .stdout("Привіт, Україно!\nДобрий день, друзі!\n");
+    assert_eq!(
+        fs::read_to_string(&invalid_path).unwrap(),
+        "Hello, world!\n\n"
+    );
+}
+
+#[test]
+fn stdin_decodes_invalid_utf8_lossily() {
+    let mut input = "Привіт, Україно!\r\n".as_bytes().to_vec();
+    input.extend_from_slice(b"\xff\xfe ");
+    input.extend_from_slice("Добрий день, друзі!\nHello, world!\n".as_bytes());
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--lang")
+        .arg("uk")
+        .write_stdin(input);
+
+    cmd.assert()
+        .success()
+        .stdout("Привіт, Україно!\n\u{FFFD}\u{FFFD} Добрий день, друзі!\n")
+        .stderr(contains("replaced invalid UTF-8 in 1 line(s)"));
+}
+
+#[test]
+fn clean_profile_selects_cleaning_steps() {
+    let tmp = tempdir().unwrap();
+    let profile_path = tmp.path().join("digits.toml");
+    fs::write(
+        &profile_path,
+        "[[steps]]\nstep = \"strip_symbols\"\nkeep_digits = true\n\n[[steps]]\nstep = \"trim\"\n",
+    )
+    .unwrap();
+    let input = "[шум] Привіт, Україно! 😊 123\n";
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--clean")
+        .arg("--clean-profile")
+        .arg("asr")
+        .write_stdin(input);
+    cmd.assert().success().stdout("привіт, україно!\n");
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--clean")
+        .arg("--clean-profile")
+        .arg(&profile_path)
+        .write_stdin(input);
+    cmd.assert()
+        .success()
+        .stdout("[шум] Привіт, Україно!  123\n");
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--clean")
+        .arg("--clean-profile")
+        .arg(tmp.path().join("missing.toml"));
+    cmd.assert()
+        .failure()
+        .stderr(contains("built-in profiles are default, asr, web, minimal"));
+}
+
+#[test]
+fn normalize_canonicalizes_text_before_detection() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+
+    write_custom_parquet(
+        &in_path,
+        "transcription",
+        &[
+            Some("Мі\u{0438}\u{0306} п`ять — сім'я"),
+            Some("Hello, world!"),
+        ],
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--normalize")
+        .arg("nfc")
+        .arg("--apostrophe")
+        .arg("ʼ")
+        .arg("--dash")
+        .arg("-");
+
+    cmd.assert().success();
+
+    let df = read_parquet(&out_path).unwrap();
+    let texts: Vec<_> = df
+        .column("transcription")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(texts, vec![Some("Мій пʼять - сімʼя")]);
+}
+
+#[test]
+fn fix_homoglyphs_repairs_mixed_script_words() {
+    let tmp = tempdir().unwrap();
+    let report_path = tmp.path().join("report.json");
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--fix-homoglyphs")
+        .env("RUST_LOG", "info")
+        .write_stdin("Пpивіт, cвіте! Як спpaви?\n");
+
+    cmd.assert()
+        .success()
+        .stdout("Привіт, світе! Як справи?\n")
+        .stderr(contains("Repaired 4 Latin/Cyrillic homoglyph(s)"));
+
+    let in_path = tmp.path().join("in.parquet");
+    let out_path = tmp.path().join("out.parquet");
+    write_custom_parquet(&in_path, "transcription", &[Some("Пpивіт, cвіте!")]).unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--fix-homoglyphs")
+        .arg("--report")
+        .arg(&report_path);
+
+    cmd.assert().success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["totals"]["repaired_homoglyphs"], 2);
+    let df = read_parquet(&out_path).unwrap();
+    let texts: Vec<_> = df
+        .column("transcription")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(texts, vec![Some("Привіт, світе!")]);
+}
+
+#[test]
+fn strip_removes_markup_before_cleaning() {
+    let input = "Дивіться https://example.com/news, пишіть admin@example.com #новини\n";
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin")
+        .arg("--clean")
+        .arg("--strip")
+        .arg("urls,emails")
+        .arg("--strip")
+        .arg("hashtags")
+        .write_stdin(input);
+    cmd.assert().success().stdout("Дивіться , пишіть\n");
+
+    // Without --strip the leftover URL and e-mail letters outweigh the Ukrainian words.
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("--stdin").arg("--clean").write_stdin(input);
+    cmd.assert().success().stdout("");
+}
+
+#[test]
+fn ndjson_outputs_write_one_object_per_row() {
+    let tmp = tempdir().unwrap();
+    let in_path = tmp.path().join("in.jsonl");
+    let out_path = tmp.path().join("out.jsonl");
+    let invalid_path = tmp.path().join("invalid.jsonl");
+
+    fs::write(
+        &in_path,
+        concat!(
+            "{\"id\": 0, \"transcription\": \"Привіт світ!\"}\n",
+            "{\"id\": 1, \"transcription\": \"Hello, world!\"}\n",
+            "{\"id\": 2, \"transcription\": null}\n",
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("babylonify").unwrap();
+    cmd.arg("-i")
+        .arg(&in_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--output-invalid")
+        .arg(&invalid_path);
+
+    cmd.assert().success();
+
+    assert_eq!(
+        fs::read_to_string(&out_path).unwrap(),
+        "{\"id\":0,\"transcription\":\"Привіт світ!\"}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&invalid_path).unwrap(),
+        concat!(
+            "{\"id\":1,\"transcription\":\"Hello, world!\",\"reject_reason\":\"wrong_language\"}\n",
+            "{\"id\":2,\"transcription\":null,\"reject_reason\":\"null\"}\n",
+        )
+    );
+}